@@ -0,0 +1,210 @@
+use crate::core::info::MATE_BOUND;
+use crate::core::movepick::{MovePicker, is_capture};
+use crate::core::search::{MAX_PLY, Search};
+use crate::core::selectivity::{FUTILITY_MARGINS, LMR_FULL_DEPTH_MOVES, REVERSE_FUTILITY_MARGIN};
+use crate::core::tt::Bound;
+use crate::models::board::ChessBoard;
+
+/// Score for getting mated at the root, mate in N is encoded as `MATE_SCORE - ply`
+pub const MATE_SCORE: i32 = 100_000;
+pub const DRAW_SCORE: i32 = 0;
+/// Bound for the search window, bigger than any score the search can return
+pub const INFINITY: i32 = MATE_SCORE + 1;
+
+impl Search {
+    /// Negamax alpha-beta search with principal variation search (PVS).
+    /// Null-move pruning, late move reductions, (reverse) futility pruning and check extensions
+    /// are applied as enabled in `selectivity`.
+    ///
+    /// Returns the score from the view of the side to move. `ply` is the distance to the root,
+    /// so shorter mates are preferred over longer ones.
+    /// If the search gets stopped the returned score is meaningless.
+    pub fn alpha_beta(
+        &mut self,
+        board: &mut ChessBoard,
+        depth: u8,
+        ply: u16,
+        mut alpha: i32,
+        beta: i32,
+    ) -> i32 {
+        if depth == 0 {
+            return self.quiescence(board, ply, alpha, beta);
+        }
+        self.nodes += 1;
+        if self.nodes & 1023 == 0 {
+            self.check_limits();
+        }
+        if self.stopped {
+            return 0;
+        }
+        self.seldepth = self.seldepth.max(ply);
+        self.pv_table[ply as usize].clear();
+        if ply as usize >= MAX_PLY {
+            return board.evaluate_relative();
+        }
+
+        // only nodes searched with a null window get cut off or pruned, a cutoff at a PV node would cut the PV short
+        let pv_node = beta - alpha > 1;
+        let key = board.get_hash();
+        let tt_entry = self.tt.probe(key, ply);
+        if let Some(entry) = tt_entry
+            && !pv_node
+            && entry.depth >= depth
+        {
+            match entry.bound {
+                Bound::Exact => return entry.score,
+                Bound::Lower if entry.score >= beta => return entry.score,
+                Bound::Upper if entry.score <= alpha => return entry.score,
+                _ => {}
+            }
+        }
+
+        let in_check = board.is_in_check();
+        let sel = self.selectivity;
+        // pruning close to a mate would hide it
+        let can_prune = !pv_node && !in_check && beta.abs() < MATE_BOUND;
+        let static_eval = board.evaluate_relative();
+
+        // reverse futility pruning, the position is so good that a quiet move keeps it above beta
+        if sel.reverse_futility
+            && can_prune
+            && depth <= 3
+            && static_eval - REVERSE_FUTILITY_MARGIN * depth as i32 >= beta
+        {
+            return static_eval;
+        }
+
+        // null-move pruning, if passing the turn still fails high a real move would as well.
+        // Not done in pawn endings, where passing might be the only way to avoid a zugzwang
+        if sel.null_move
+            && can_prune
+            && depth >= 3
+            && !self.null_moves[ply as usize]
+            && static_eval >= beta
+            && board.has_non_pawn_material(board.side_to_move())
+        {
+            let reduction = 2 + depth / 4;
+            let undo = board.make_null_move();
+            self.null_moves[ply as usize + 1] = true;
+            let eval = -self.alpha_beta(
+                board,
+                depth.saturating_sub(1 + reduction),
+                ply + 1,
+                -beta,
+                -beta + 1,
+            );
+            self.null_moves[ply as usize + 1] = false;
+            board.unmake_null_move(undo);
+            if self.stopped {
+                return 0;
+            }
+            if eval >= beta {
+                // a mate found after passing the turn isn't proven
+                return if eval >= MATE_BOUND { beta } else { eval };
+            }
+        }
+
+        let all_moves = board.all_possible_moves();
+        if all_moves.is_empty() {
+            return if in_check {
+                -MATE_SCORE + ply as i32
+            } else {
+                DRAW_SCORE
+            };
+        }
+
+        // searching the best move of an earlier search first
+        let hash_mv = tt_entry.and_then(|entry| entry.best_mv);
+        let mut picker = MovePicker::new(board, all_moves, hash_mv, self.killers[ply as usize]);
+
+        let alpha_orig = alpha;
+        let mut best_eval = -INFINITY;
+        let mut best_mv = None;
+        // futility pruning, near the horizon quiet moves can't raise alpha if the position is far below it
+        let futile = sel.futility
+            && can_prune
+            && (depth as usize) < FUTILITY_MARGINS.len()
+            && static_eval + FUTILITY_MARGINS[depth as usize] <= alpha;
+        let mut move_count = 0;
+        while let Some(mv) = picker.next(&self.history) {
+            let quiet = !is_capture(board, mv);
+            let undo = board.make_move(mv);
+            let gives_check = board.is_in_check();
+            move_count += 1;
+            if futile && quiet && !gives_check && move_count > 1 {
+                board.unmake_move(mv, undo);
+                continue;
+            }
+            // a perpetual check would extend forever, so checks deep in the tree aren't extended any more
+            let extend = sel.check_extensions && gives_check && ply < 2 * self.root_depth as u16;
+            let new_depth = depth - 1 + u8::from(extend);
+            let eval = if move_count == 1 {
+                -self.alpha_beta(board, new_depth, ply + 1, -beta, -alpha)
+            } else {
+                // late move reductions, quiet moves ordered late are searched less deep first
+                let reduction = if sel.lmr
+                    && quiet
+                    && !in_check
+                    && !gives_check
+                    && depth >= 3
+                    && move_count > LMR_FULL_DEPTH_MOVES
+                    && !self.killers[ply as usize].contains(&Some(mv))
+                {
+                    if move_count > 2 * LMR_FULL_DEPTH_MOVES {
+                        2
+                    } else {
+                        1
+                    }
+                } else {
+                    0
+                };
+                // searching with a null window first, only re-searching if the move might be better
+                let mut eval = -self.alpha_beta(
+                    board,
+                    new_depth.saturating_sub(reduction),
+                    ply + 1,
+                    -alpha - 1,
+                    -alpha,
+                );
+                if reduction > 0 && eval > alpha {
+                    eval = -self.alpha_beta(board, new_depth, ply + 1, -alpha - 1, -alpha);
+                }
+                if eval > alpha && eval < beta {
+                    -self.alpha_beta(board, new_depth, ply + 1, -beta, -alpha)
+                } else {
+                    eval
+                }
+            };
+            board.unmake_move(mv, undo);
+            if eval > best_eval {
+                best_eval = eval;
+                best_mv = Some(mv);
+                if eval > alpha {
+                    alpha = eval;
+                    self.update_pv(ply as usize, mv);
+                    if alpha >= beta {
+                        if !is_capture(board, mv)
+                            && let Some(piece) = board.piece_on(mv.get_curr_square_as_index())
+                        {
+                            self.store_killer(ply as usize, mv);
+                            self.history.update(piece, mv, depth);
+                        }
+                        break;
+                    }
+                }
+            }
+        }
+
+        if !self.stopped {
+            let bound = if best_eval >= beta {
+                Bound::Lower
+            } else if best_eval > alpha_orig {
+                Bound::Exact
+            } else {
+                Bound::Upper
+            };
+            self.tt.store(key, best_mv, best_eval, depth, bound, ply);
+        }
+        best_eval
+    }
+}
@@ -0,0 +1,55 @@
+use crate::models::board::Bitboard;
+
+/// Attackmasks of a knight, indexed by square
+pub static KNIGHT_ATTACKS: [Bitboard; 64] = generate_attacks(&[
+    (-2, -1),
+    (-2, 1),
+    (-1, -2),
+    (-1, 2),
+    (1, -2),
+    (1, 2),
+    (2, -1),
+    (2, 1),
+]);
+
+/// Attackmasks of a king, indexed by square
+pub static KING_ATTACKS: [Bitboard; 64] = generate_attacks(&[
+    (-1, -1),
+    (-1, 0),
+    (-1, 1),
+    (0, -1),
+    (0, 1),
+    (1, -1),
+    (1, 0),
+    (1, 1),
+]);
+
+/// Attackmasks of a pawn, indexed by color and square. A pawn on its last rank doesn't attack anything.
+pub static PAWN_ATTACKS: [[Bitboard; 64]; 2] = [
+    generate_attacks(&[(-1, -1), (-1, 1)]),
+    generate_attacks(&[(1, -1), (1, 1)]),
+];
+
+/// Generates the attackmasks of a piece jumping by the offsets at compile time. <br>
+/// An offset is given as (rows, files), where a row is a rank downwards from the 8th rank,
+/// jumps leaving the board are dropped.
+const fn generate_attacks(offsets: &[(i16, i16)]) -> [Bitboard; 64] {
+    let mut table = [Bitboard::new(0); 64];
+    let mut sq_index = 0;
+    while sq_index < 64 {
+        let mut attackmask = 0u64;
+        let mut i = 0;
+        while i < offsets.len() {
+            let row = (sq_index >> 3) + offsets[i].0;
+            let file = (sq_index & 0b111) + offsets[i].1;
+            if row >= 0 && row < 8 && file >= 0 && file < 8 {
+                // index 0 (a8) is the most significant bit, see `Bitboard`
+                attackmask |= 1 << (63 - (row * 8 + file));
+            }
+            i += 1;
+        }
+        table[sq_index as usize] = Bitboard::new(attackmask);
+        sq_index += 1;
+    }
+    table
+}
@@ -1,31 +1,97 @@
+use crate::core::limits::GoParams;
+use crate::core::search::Search;
+use crate::core::tt::Bound;
 use crate::models::{board::ChessBoard, chessmove::ChessMove};
 
-impl ChessBoard {
-    pub fn best_mv(&self, depth: u8) -> ChessMove {
-        let mut best_mv = ChessMove::new(0u16);
-        // Looking for the best eval for the color that the engine plays as
-        let mut best_eval = if self.get_white_to_move() {
-            i32::MIN
-        } else {
-            i32::MAX
-        };
-        for mv in self.all_possible_moves() {
-            let curr_eval = self.with_move(mv).minimax(depth);
-            if self.get_white_to_move() {
-                if best_eval <= curr_eval {
-                    best_eval = curr_eval;
-                    best_mv = mv;
-                }
+/// A root move with its score and principal variation, starting with the move itself
+pub type PvLine = (ChessMove, i32, Vec<ChessMove>);
+
+impl Search {
+    /// Searches every root move `depth` plies deep within the window `alpha`..`beta`,
+    /// starting with the best moves of the previous iteration. <br>
+    /// Once the best lines are found, the other moves are searched with a null window (PVS)
+    /// and only get searched again if they turn out to be better. <br>
+    /// Returns the best `multi_pv` lines ranked by score and whether their scores are exact,
+    /// a move scoring at least `beta` ends the search as lower bound, no move scoring above `alpha` gives an upper bound.
+    /// Returns `None` if the search got stopped before the iteration was completed.
+    pub(crate) fn root_search(
+        &mut self,
+        depth: u8,
+        prev_lines: &[PvLine],
+        alpha: i32,
+        beta: i32,
+    ) -> Option<(Vec<PvLine>, Bound)> {
+        self.root_depth = depth;
+        let mut board = *self.board();
+        let mut all_moves = board.all_possible_moves();
+        for (i, line) in prev_lines.iter().enumerate() {
+            if let Some(pos) = all_moves.iter().position(|&mv| mv == line.0) {
+                all_moves[i..=pos].rotate_right(1);
+            }
+        }
+
+        let multi_pv = self.multi_pv.clamp(1, all_moves.len().max(1));
+        let mut lines: Vec<PvLine> = Vec::with_capacity(multi_pv + 1);
+        self.pv_table[0].clear();
+        for mv in all_moves {
+            // Only moves that can get into the best lines need an exact score,
+            // so the window is bounded by the worst of the best lines
+            let full = lines.len() < multi_pv;
+            let alpha = if full {
+                alpha
             } else {
-                if best_eval >= curr_eval {
-                    best_eval = curr_eval;
-                    best_mv = mv;
+                lines[multi_pv - 1].1.max(alpha)
+            };
+            let undo = board.make_move(mv);
+            let curr_eval = if full {
+                -self.alpha_beta(&mut board, depth - 1, 1, -beta, -alpha)
+            } else {
+                let eval = -self.alpha_beta(&mut board, depth - 1, 1, -alpha - 1, -alpha);
+                if eval > alpha && eval < beta {
+                    -self.alpha_beta(&mut board, depth - 1, 1, -beta, -alpha)
+                } else {
+                    eval
+                }
+            };
+            board.unmake_move(mv, undo);
+            if self.stopped {
+                return None;
+            }
+            if curr_eval > alpha {
+                self.update_pv(0, mv);
+                let pos = lines
+                    .iter()
+                    .position(|line| line.1 < curr_eval)
+                    .unwrap_or(lines.len());
+                lines.insert(pos, (mv, curr_eval, self.pv_table[0].clone()));
+                lines.truncate(multi_pv);
+                if curr_eval >= beta {
+                    return Some((lines, Bound::Lower));
                 }
             }
         }
-        if best_mv.to_u16() == 0 {
-            panic!("Cannot create move with value 0")
+        if lines.is_empty() {
+            return Some((lines, Bound::Upper));
         }
-        best_mv
+        Some((lines, Bound::Exact))
+    }
+}
+
+impl ChessBoard {
+    /// Searches the position with a fixed depth and returns the best move. <br>
+    /// The depth counts every ply including the root move, `best_mv(1)` only looks at the moves themselves.
+    pub fn best_mv(&self, depth: u8) -> ChessMove {
+        Search::new(*self, GoParams::fixed_depth(depth))
+            .run()
+            .expect("Cannot find a move in a position without legal moves")
+    }
+
+    /// Searches the position with a fixed depth and returns the best `count` moves
+    /// with their score and principal variation, ranked from best to worst
+    pub fn multi_pv(&self, depth: u8, count: usize) -> Vec<PvLine> {
+        let mut search = Search::new(*self, GoParams::fixed_depth(depth));
+        search.set_multi_pv(count);
+        search.run();
+        search.lines().to_vec()
     }
 }
@@ -0,0 +1,74 @@
+use std::fmt;
+use std::time::Duration;
+
+use crate::core::alphabeta::MATE_SCORE;
+use crate::core::tt::Bound;
+use crate::models::chessmove::ChessMove;
+
+/// Scores above this bound (or below its negation) are reported as mate scores
+pub const MATE_BOUND: i32 = MATE_SCORE - 1_000;
+
+/// Statistics of a completed iteration, printed as UCI `info` line
+#[derive(Clone, Debug, PartialEq)]
+pub struct SearchInfo {
+    pub depth: u8,
+    pub seldepth: u16,
+    /// Rank of the line, only reported when searching more than one line
+    pub multipv: Option<usize>,
+    pub score: i32,
+    /// The score is only a bound if it fell outside the aspiration window
+    pub bound: Bound,
+    pub nodes: u64,
+    pub time: Duration,
+    /// Filled entries of the transposition table in permill
+    pub hashfull: u16,
+    pub pv: Vec<ChessMove>,
+}
+
+impl SearchInfo {
+    pub fn nps(&self) -> u64 {
+        let millis = self.time.as_millis().max(1) as u64;
+        self.nodes * 1000 / millis
+    }
+}
+
+impl fmt::Display for SearchInfo {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "info depth {} seldepth {}", self.depth, self.seldepth)?;
+        if let Some(multipv) = self.multipv {
+            write!(f, " multipv {}", multipv)?;
+        }
+        write!(f, " score {}", uci_score(self.score))?;
+        match self.bound {
+            Bound::Exact => {}
+            Bound::Lower => write!(f, " lowerbound")?,
+            Bound::Upper => write!(f, " upperbound")?,
+        }
+        write!(
+            f,
+            " nodes {} nps {} time {} hashfull {}",
+            self.nodes,
+            self.nps(),
+            self.time.as_millis(),
+            self.hashfull
+        )?;
+        if !self.pv.is_empty() {
+            write!(f, " pv")?;
+            for mv in &self.pv {
+                write!(f, " {}", mv.to_str())?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Converts a score into the UCI format, `cp <x>` or `mate <y>` with y in moves, negative if the engine gets mated
+pub fn uci_score(score: i32) -> String {
+    if score > MATE_BOUND {
+        format!("mate {}", (MATE_SCORE - score + 1) / 2)
+    } else if score < -MATE_BOUND {
+        format!("mate -{}", (MATE_SCORE + score) / 2)
+    } else {
+        format!("cp {}", score)
+    }
+}
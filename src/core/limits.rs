@@ -0,0 +1,102 @@
+use std::time::Duration;
+
+/// Time reserved for communication with the GUI, subtracted from every time limit
+const MOVE_OVERHEAD_MS: u64 = 20;
+/// Assumed number of moves until the next time control, if the GUI doesn't send `movestogo`
+const DEFAULT_MOVES_TO_GO: u64 = 30;
+
+/// Parameters of the UCI `go` command, every parameter that isn't sent by the GUI is `None`
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct GoParams {
+    pub depth: Option<u8>,
+    pub nodes: Option<u64>,
+    pub movetime: Option<u64>,
+    pub wtime: Option<u64>,
+    pub btime: Option<u64>,
+    pub winc: Option<u64>,
+    pub binc: Option<u64>,
+    pub movestogo: Option<u64>,
+    pub infinite: bool,
+    pub ponder: bool,
+    pub mate: Option<u8>,
+}
+
+/// Time the search may spend on the current move
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct TimeBudget {
+    /// No new iteration is started after this time has passed
+    pub soft: Duration,
+    /// The running iteration gets aborted after this time has passed
+    pub hard: Duration,
+}
+
+impl GoParams {
+    /// Parses a `go` command, e.g. `go wtime 300000 btime 300000 winc 2000 binc 2000`. <br>
+    /// Unknown tokens and values that can't be parsed are ignored.
+    pub fn parse(cmd: &str) -> Self {
+        let mut params = Self::default();
+        let mut tokens = cmd.split_whitespace();
+        while let Some(token) = tokens.next() {
+            match token {
+                "depth" => params.depth = tokens.next().and_then(|t| t.parse().ok()),
+                "nodes" => params.nodes = tokens.next().and_then(|t| t.parse().ok()),
+                "movetime" => params.movetime = tokens.next().and_then(parse_millis),
+                "wtime" => params.wtime = tokens.next().and_then(parse_millis),
+                "btime" => params.btime = tokens.next().and_then(parse_millis),
+                "winc" => params.winc = tokens.next().and_then(parse_millis),
+                "binc" => params.binc = tokens.next().and_then(parse_millis),
+                "movestogo" => params.movestogo = tokens.next().and_then(|t| t.parse().ok()),
+                "mate" => params.mate = tokens.next().and_then(|t| t.parse().ok()),
+                "infinite" => params.infinite = true,
+                "ponder" => params.ponder = true,
+                _ => {}
+            }
+        }
+        params
+    }
+
+    /// Parameters for a search limited only by its depth
+    pub fn fixed_depth(depth: u8) -> Self {
+        Self {
+            depth: Some(depth),
+            ..Self::default()
+        }
+    }
+
+    /// Allocates the time for the current move, `None` if the search is not limited by time
+    pub fn time_budget(&self, white_to_move: bool) -> Option<TimeBudget> {
+        if self.infinite {
+            return None;
+        }
+        if let Some(movetime) = self.movetime {
+            let time = Duration::from_millis(movetime.saturating_sub(MOVE_OVERHEAD_MS).max(1));
+            return Some(TimeBudget {
+                soft: time,
+                hard: time,
+            });
+        }
+
+        let (time_left, inc) = if white_to_move {
+            (self.wtime?, self.winc.unwrap_or(0))
+        } else {
+            (self.btime?, self.binc.unwrap_or(0))
+        };
+        let time_left = time_left.saturating_sub(MOVE_OVERHEAD_MS).max(1);
+        let moves_to_go = self.movestogo.unwrap_or(DEFAULT_MOVES_TO_GO).clamp(1, 50);
+
+        // an even share of the remaining time plus most of the increment,
+        // the hard limit allows exceeding it for difficult positions but never gets close to flagging
+        let base = time_left / moves_to_go + inc * 3 / 4;
+        let hard = (base * 3).min(time_left * 8 / 10).max(1);
+        let soft = base.min(hard);
+        Some(TimeBudget {
+            soft: Duration::from_millis(soft),
+            hard: Duration::from_millis(hard),
+        })
+    }
+}
+
+/// Some GUIs send negative times when the engine is already out of time
+fn parse_millis(token: &str) -> Option<u64> {
+    token.parse::<i64>().ok().map(|ms| ms.max(0) as u64)
+}
@@ -0,0 +1,241 @@
+use std::sync::LazyLock;
+
+use crate::models::board::{Bitboard, ChessBoard};
+
+/// Magic numbers of the bishops, indexed by square. <br>
+/// Found with a random search for this bitboard layout, so every occupancy maps to a table entry with the right attackmask.
+const BISHOP_MAGICS: [u64; 64] = [
+    0x8008_0298_0200_2200,
+    0x0202_0430_8202_0416,
+    0x2042_0120_2002_8080,
+    0x4000_0001_2002_5400,
+    0x0820_5040_1884_0414,
+    0xe024_4043_0098_0400,
+    0x4200_0884_0088_0400,
+    0x2083_0080_5042_1008,
+    0x1008_1084_0850_4008,
+    0x0012_1001_0800_8001,
+    0x0007_a004_1020_8000,
+    0x0818_8012_2202_1001,
+    0x0010_2808_2088_0010,
+    0x4300_8600_8c11_2061,
+    0x2402_8088_8860_9880,
+    0x0115_0828_04c4_0800,
+    0x0004_0440_4042_0203,
+    0x0520_0401_4040_6208,
+    0x101c_0488_0202_0041,
+    0x0200_1060_1040_0202,
+    0xcc00_0840_1041_0a00,
+    0x0000_1110_8812_5000,
+    0x0009_0888_2040_d417,
+    0x0040_8410_0805_4110,
+    0x0098_0604_2880_5300,
+    0x1402_4800_4801_0401,
+    0x04b0_0a00_2082_00a8,
+    0x30c0_2100_1081_0040,
+    0x8000_0808_0046_0a00,
+    0x0030_2030_0008_0882,
+    0x0068_0209_2122_4800,
+    0x0010_3804_0009_1054,
+    0x0040_4080_0442_1803,
+    0x2818_4108_1041_0800,
+    0x0210_0980_8848_0c04,
+    0x4281_0100_2010_4000,
+    0x0404_0401_1802_0808,
+    0x0202_0800_0100_4400,
+    0x1002_5010_028c_0821,
+    0x0008_4100_2014_0900,
+    0x0800_8020_2088_0824,
+    0x0000_8080_4804_1000,
+    0x0022_0009_004e_0a62,
+    0x8004_0104_80a0_0008,
+    0x000c_0008_0140_10d0,
+    0x2022_1021_0204_0500,
+    0x0202_0020_0222_0a01,
+    0x0208_2c40_0208_8202,
+    0x0000_0d04_0202_0200,
+    0x000c_a402_1111_0800,
+    0x02c1_0101_0840_1404,
+    0x204a_0202_1000_0212,
+    0x9001_4404_0880_1004,
+    0xa028_1104_0082_1008,
+    0x2400_0214_8114_1100,
+    0x8000_0902_0818_2100,
+    0x0010_1042_1004_2000,
+    0x4014_2404_0208_0010,
+    0x0022_0802_0a00_0108,
+    0x0104_5040_0020_0014,
+    0x1431_0410_8004_4014,
+    0x0004_0114_1110_4201,
+    0x2010_1020_8100_4004,
+    0x4410_301c_8284_1041,
+];
+
+/// Magic numbers of the rooks, indexed by square
+const ROOK_MAGICS: [u64; 64] = [
+    0x1002_0904_0422_4882,
+    0x6808_1042_0800_8124,
+    0x1005_0082_0804_0001,
+    0x00c2_0008_8410_a002,
+    0x48aa_6049_8500_1001,
+    0x2008_8200_2008_1042,
+    0x0040_1020_4000_8101,
+    0x1a82_0020_8011_0842,
+    0x8381_0410_8900_4200,
+    0x2001_0002_0084_4100,
+    0x2000_8002_0004_0080,
+    0x4208_0080_0400_0880,
+    0x0210_0480_1008_0080,
+    0x2410_0080_1020_0080,
+    0x0080_4100_8200_2200,
+    0x0040_2041_0080_0100,
+    0x2024_0084_0042_0001,
+    0x0221_0402_0001_0100,
+    0x0040_0400_0200_8080,
+    0x0102_6801_0005_0010,
+    0x8010_1000_0800_8080,
+    0x0000_1200_8022_0040,
+    0x0030_5000_2000_4008,
+    0x0080_8000_4001_8023,
+    0x0150_4900_b200_0044,
+    0x0d41_0004_0500_1200,
+    0x0084_0002_0080_0480,
+    0x0410_808c_0180_0801,
+    0x0000_8110_0280_0800,
+    0x0108_2001_0100_1040,
+    0x0010_0420_0540_0450,
+    0xa000_8040_0480_0031,
+    0x0001_00a2_0010_4401,
+    0x0000_2104_0010_02c8,
+    0x0048_0200_8004_0080,
+    0x4008_0088_8044_0080,
+    0x4600_2409_0010_0100,
+    0x0820_0010_1004_0200,
+    0x1001_0021_0040_0080,
+    0x0000_4000_8020_8000,
+    0x0008_0200_0040_8104,
+    0x1000_0400_5001_0208,
+    0x0000_0801_0440_2010,
+    0x6000_8180_0c00_0800,
+    0x0000_8080_0800_1000,
+    0x8010_8080_2000_1000,
+    0x1b01_8180_2000_4013,
+    0x0880_0040_2000_4000,
+    0x0402_0002_1040_a504,
+    0x0404_0010_0802_b104,
+    0x0801_0008_0401_0002,
+    0x0000_8008_0004_0080,
+    0x2482_0008_4200_2010,
+    0x0004_8020_0210_0088,
+    0x0002_0040_2100_8200,
+    0x0400_8000_4000_8022,
+    0x1200_0100_8042_0024,
+    0x0400_0810_1244_8504,
+    0x0100_0804_0002_0100,
+    0x4600_1014_2008_4200,
+    0x6080_0408_0080_1000,
+    0x0900_0b00_40a0_0010,
+    0x0040_0010_0040_2000,
+    0x8080_0140_0032_8820,
+];
+
+const RANK_8: u64 = 0xFF00_0000_0000_0000;
+const RANK_1: u64 = 0x0000_0000_0000_00FF;
+const FILE_A: u64 = 0x8080_8080_8080_8080;
+const FILE_H: u64 = 0x0101_0101_0101_0101;
+
+/// Lookup tables of the slider attackmasks, filled on first use
+static BISHOP_TABLE: LazyLock<SliderTable> =
+    LazyLock::new(|| SliderTable::new(&BISHOP_MAGICS, ChessBoard::calc_bishop_rays));
+static ROOK_TABLE: LazyLock<SliderTable> =
+    LazyLock::new(|| SliderTable::new(&ROOK_MAGICS, ChessBoard::calc_rook_rays));
+
+/// Attackmask of a bishop on the square, blocked by the pieces in `occupancy`
+pub fn bishop_attacks(sq_index: u16, occupancy: Bitboard) -> Bitboard {
+    BISHOP_TABLE.attacks(sq_index, occupancy)
+}
+
+/// Attackmask of a rook on the square, blocked by the pieces in `occupancy`
+pub fn rook_attacks(sq_index: u16, occupancy: Bitboard) -> Bitboard {
+    ROOK_TABLE.attacks(sq_index, occupancy)
+}
+
+/// Attackmask of a queen on the square, blocked by the pieces in `occupancy`
+pub fn queen_attacks(sq_index: u16, occupancy: Bitboard) -> Bitboard {
+    bishop_attacks(sq_index, occupancy) | rook_attacks(sq_index, occupancy)
+}
+
+/// Attackmasks of a slider for every square and every occupancy of the squares it could be blocked on,
+/// see https://www.chessprogramming.org/Magic_Bitboards
+struct SliderTable {
+    squares: [MagicEntry; 64],
+    attacks: Vec<Bitboard>,
+}
+
+/// Position of the attackmasks of a square in the table
+#[derive(Clone, Copy, Default)]
+// the PEXT lookup only needs the mask
+#[cfg_attr(feature = "pext", allow(dead_code))]
+struct MagicEntry {
+    /// Squares that can block the slider, the edges of the board don't block any further square
+    mask: u64,
+    magic: u64,
+    shift: u32,
+    offset: usize,
+}
+
+impl MagicEntry {
+    /// Index of the attackmask for the occupancy, relative to the offset of the square
+    #[cfg(not(all(feature = "pext", target_arch = "x86_64", target_feature = "bmi2")))]
+    fn index(&self, occupancy: u64) -> usize {
+        ((occupancy & self.mask).wrapping_mul(self.magic) >> self.shift) as usize
+    }
+
+    /// Index of the attackmask for the occupancy, relative to the offset of the square. <br>
+    /// The blocking squares are extracted with the PEXT instruction, the magic numbers aren't needed.
+    #[cfg(all(feature = "pext", target_arch = "x86_64", target_feature = "bmi2"))]
+    fn index(&self, occupancy: u64) -> usize {
+        // SAFETY: the BMI2 instruction set is enabled at compile time
+        unsafe { std::arch::x86_64::_pext_u64(occupancy, self.mask) as usize }
+    }
+}
+
+impl SliderTable {
+    /// Fills the table with the attackmasks calculated by walking along the rays of the slider
+    fn new(magics: &[u64; 64], rays: fn(u16, Bitboard) -> Bitboard) -> Self {
+        let mut squares = [MagicEntry::default(); 64];
+        let mut attacks = Vec::new();
+        for (sq_index, entry) in squares.iter_mut().enumerate() {
+            let sq_index = sq_index as u16;
+            let rank = RANK_8 >> ((sq_index & !0b111) as u64);
+            let file = FILE_A >> ((sq_index & 0b111) as u64);
+            let edges = ((RANK_1 | RANK_8) & !rank) | ((FILE_A | FILE_H) & !file);
+            let mask = rays(sq_index, Bitboard::new(0)).to_u64() & !edges;
+            let bits = mask.count_ones();
+            *entry = MagicEntry {
+                mask,
+                magic: magics[sq_index as usize],
+                shift: 64 - bits,
+                offset: attacks.len(),
+            };
+            attacks.resize(attacks.len() + (1 << bits), Bitboard::new(0));
+
+            // iterating over every subset of the mask (Carry-Rippler)
+            let mut occupancy = 0u64;
+            loop {
+                attacks[entry.offset + entry.index(occupancy)] =
+                    rays(sq_index, Bitboard::new(occupancy));
+                occupancy = occupancy.wrapping_sub(mask) & mask;
+                if occupancy == 0 {
+                    break;
+                }
+            }
+        }
+        Self { squares, attacks }
+    }
+
+    fn attacks(&self, sq_index: u16, occupancy: Bitboard) -> Bitboard {
+        let entry = &self.squares[sq_index as usize];
+        self.attacks[entry.offset + entry.index(occupancy.to_u64())]
+    }
+}
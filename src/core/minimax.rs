@@ -1,10 +1,12 @@
 use std::cmp;
 
-use crate::models::board::ChessBoard;
+use crate::models::{board::ChessBoard, piece::Piece};
+// plain minimax is no longer used by the engine, it is kept as a reference for testing the alpha-beta search
+#[allow(dead_code)]
 impl ChessBoard {
     pub fn minimax(&self, depth: u8) -> i32 {
         if depth == 0 {
-            return self.evaluate_position();
+            return self.quiescence_minimax();
         };
         let all_moves = self.all_possible_moves();
         // If the tree gets too big, reduce the depth so performance gets safed
@@ -17,14 +19,56 @@ impl ChessBoard {
                 let eval = self.with_move(mv).minimax(depth - 1);
                 max_eval = cmp::max(max_eval, eval);
             }
-            return max_eval;
+            max_eval
         } else {
             let mut min_eval = i32::MAX;
             for mv in all_moves {
                 let eval = self.with_move(mv).minimax(depth - 1);
                 min_eval = cmp::min(min_eval, eval);
             }
-            return min_eval;
+            min_eval
+        }
+    }
+
+    /// Minimax over captures and promotions (every evasion when in check) without any pruning
+    /// apart from alpha-beta, which keeps the exact value of the position. <br>
+    /// Plain minimax is too slow for the capture sequences of middlegame positions.
+    pub fn quiescence_minimax(&self) -> i32 {
+        self.quiescence_alpha_beta(i32::MIN, i32::MAX)
+    }
+
+    fn quiescence_alpha_beta(&self, mut alpha: i32, mut beta: i32) -> i32 {
+        let white = self.get_white_to_move();
+        let (mut moves, mut best_eval) = if self.is_in_check() {
+            let worst = if white { i32::MIN } else { i32::MAX };
+            (self.all_possible_moves(), worst)
+        } else {
+            // standing pat, the side to move doesn't have to capture
+            (self.capture_moves(), self.evaluate_position())
         };
+        // most valuable victim first, so the alpha-beta bounds get tight early
+        let value = |sq: u16| self.piece_on(sq).map_or(0, Piece::value);
+        moves.sort_by_key(|mv| {
+            let victim = value(mv.get_dest_square_as_index());
+            let attacker = value(mv.get_curr_square_as_index());
+            (-victim, attacker)
+        });
+        for mv in moves {
+            if white {
+                alpha = alpha.max(best_eval);
+            } else {
+                beta = beta.min(best_eval);
+            }
+            if alpha >= beta {
+                break;
+            }
+            let eval = self.with_move(mv).quiescence_alpha_beta(alpha, beta);
+            best_eval = if white {
+                cmp::max(best_eval, eval)
+            } else {
+                cmp::min(best_eval, eval)
+            };
+        }
+        best_eval
     }
 }
@@ -1,4 +1,17 @@
+pub mod alphabeta;
+pub mod attacktables;
 pub mod bestmv;
+pub mod info;
+pub mod limits;
+pub mod magic;
 pub mod minimax;
 pub mod movegen;
 pub mod movemasks;
+pub mod movepick;
+pub mod output;
+pub mod quiescence;
+pub mod search;
+pub mod searchthread;
+pub mod see;
+pub mod selectivity;
+pub mod tt;
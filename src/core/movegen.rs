@@ -1,379 +1,204 @@
-use crate::models::board::ChessBoard;
+use crate::core::magic;
+use crate::models::board::{Bitboard, ChessBoard};
 use crate::models::chessmove::{CastleMove, ChessMove};
-use crate::models::piece::{Piece, castling};
+use crate::models::movelist::MoveList;
+use crate::models::piece::{Color, Piece, Role, castling};
+
+/// Castling move with the squares between king and rook, which have to be empty,
+/// and the squares the king passes, which must not be attacked
+#[rustfmt::skip]
+const CASTLING_PATHS: [(u8, ChessMove, u64, u64); 4] = [
+    // f1, g1
+    (castling::WHITE_K, CastleMove::WHITE_K, 0x06, 0x06),
+    // d1, c1, b1
+    (castling::WHITE_Q, CastleMove::WHITE_Q, 0x70, 0x30),
+    // f8, g8
+    (castling::BLACK_K, CastleMove::BLACK_K, 0x06 << 56, 0x06 << 56),
+    // d8, c8, b8
+    (castling::BLACK_Q, CastleMove::BLACK_Q, 0x70 << 56, 0x30 << 56),
+];
 
 impl ChessBoard {
-    /// Generates possible legal moves in the current position using the move- and attackmasks
-    pub fn all_possible_moves(&self) -> Vec<ChessMove> {
-        let mut all_moves: Vec<ChessMove> = Vec::new();
-        let mut res: Vec<ChessMove> = Vec::new();
-        // only generating pieces for the playing color
-        if self.get_white_to_move() {
-            all_moves.extend(self.w_pawn_moves());
-            all_moves.extend(self.w_bishop_moves());
-            all_moves.extend(self.w_knight_moves());
-            all_moves.extend(self.w_rook_moves());
-            all_moves.extend(self.w_king_moves());
-            all_moves.extend(self.w_queen_moves());
-            // filtering out moves that expose the king to a check (pins)
-            for mv in all_moves {
-                let new_board = self.with_move(mv);
-                let w_king = new_board.get_bitboard(Piece::WhiteKing.into());
-                if (w_king & new_board.calc_b_attackmask()).to_u64() == 0 {
-                    res.push(mv);
-                }
-            }
-            return res;
-        } else {
-            all_moves.extend(self.b_pawn_moves());
-            all_moves.extend(self.b_bishop_moves());
-            all_moves.extend(self.b_knight_moves());
-            all_moves.extend(self.b_rook_moves());
-            all_moves.extend(self.b_king_moves());
-            all_moves.extend(self.b_queen_moves());
-            // filtering out moves that expose the king to a check (pins)
-            for mv in all_moves {
-                let new_board = self.with_move(mv);
-                let b_king = new_board.get_bitboard(Piece::BlackKing.into());
-                if (b_king & new_board.calc_w_attackmask()).to_u64() == 0 {
-                    res.push(mv);
-                }
-            }
-            return res;
-        }
+    /// Generates the legal moves in the current position
+    pub fn all_possible_moves(&self) -> MoveList {
+        self.legal_moves(false)
     }
 
-    pub fn w_pawn_moves(&self) -> Vec<ChessMove> {
-        let mut moves: Vec<ChessMove> = Vec::new();
-        let b_pieces = self.get_b_pieces();
-        let w_pawns = self.get_bitboard(Piece::WhitePawn.into());
-
-        for curr_sq in w_pawns {
-            for dest_sq in self.calc_w_pawn_movemask(curr_sq) {
-                // Pawn Promotion, see encoding in ChessMove docu
-                if (dest_sq >> 3) == 0 {
-                    moves.push(ChessMove::from_curr_and_dest(
-                        0b0001_000000 | curr_sq,
-                        dest_sq,
-                    ));
-                    moves.push(ChessMove::from_curr_and_dest(
-                        0b0010_000000 | curr_sq,
-                        dest_sq,
-                    ));
-                    moves.push(ChessMove::from_curr_and_dest(
-                        0b0100_000000 | curr_sq,
-                        dest_sq,
-                    ));
-                    moves.push(ChessMove::from_curr_and_dest(
-                        0b1000_000000 | curr_sq,
-                        dest_sq,
-                    ));
-                } else {
-                    moves.push(ChessMove::from_curr_and_dest(curr_sq, dest_sq))
-                }
-            }
-            for dest_sq in ChessBoard::calc_w_pawn_attackmask(curr_sq) {
-                if b_pieces.get_bit(dest_sq) || self.get_en_passant() == dest_sq {
-                    // Pawn Promotion, see encoding in ChessMove docu
-                    if (dest_sq >> 3) == 0 {
-                        moves.push(ChessMove::from_curr_and_dest(
-                            0b0001_000000 | curr_sq,
-                            dest_sq,
-                        ));
-                        moves.push(ChessMove::from_curr_and_dest(
-                            0b0010_000000 | curr_sq,
-                            dest_sq,
-                        ));
-                        moves.push(ChessMove::from_curr_and_dest(
-                            0b0100_000000 | curr_sq,
-                            dest_sq,
-                        ));
-                        moves.push(ChessMove::from_curr_and_dest(
-                            0b1000_000000 | curr_sq,
-                            dest_sq,
-                        ));
-                    } else {
-                        moves.push(ChessMove::from_curr_and_dest(curr_sq, dest_sq))
-                    }
-                }
-            }
-        }
-        moves
+    /// Generates the legal captures and promotions in the current position, used by the quiescence search
+    pub fn capture_moves(&self) -> MoveList {
+        self.legal_moves(true)
     }
 
-    pub fn b_pawn_moves(&self) -> Vec<ChessMove> {
-        let mut moves: Vec<ChessMove> = Vec::new();
-        let w_pieces = self.get_w_pieces();
-        let b_pawns = self.get_bitboard(Piece::BlackPawn.into());
-
-        for curr_sq in b_pawns {
-            for dest_sq in self.calc_b_pawn_movemask(curr_sq) {
-                if (dest_sq >> 3) == 7 {
-                    // Pawn Promotion, see encoding in ChessMove docu
-                    moves.push(ChessMove::from_curr_and_dest(
-                        0b0001_000000 | curr_sq,
-                        dest_sq,
-                    ));
-                    moves.push(ChessMove::from_curr_and_dest(
-                        0b0010_000000 | curr_sq,
-                        dest_sq,
-                    ));
-                    moves.push(ChessMove::from_curr_and_dest(
-                        0b0100_000000 | curr_sq,
-                        dest_sq,
-                    ));
-                    moves.push(ChessMove::from_curr_and_dest(
-                        0b1000_000000 | curr_sq,
-                        dest_sq,
-                    ));
-                } else {
-                    moves.push(ChessMove::from_curr_and_dest(curr_sq, dest_sq))
-                }
-            }
-            for dest_sq in ChessBoard::calc_b_pawn_attackmask(curr_sq) {
-                if w_pieces.get_bit(dest_sq) || self.get_en_passant() == dest_sq {
-                    if (dest_sq >> 3) == 7 {
-                        // Pawn Promotion, see encoding in ChessMove docu
-                        moves.push(ChessMove::from_curr_and_dest(
-                            0b0001_000000 | curr_sq,
-                            dest_sq,
-                        ));
-                        moves.push(ChessMove::from_curr_and_dest(
-                            0b0010_000000 | curr_sq,
-                            dest_sq,
-                        ));
-                        moves.push(ChessMove::from_curr_and_dest(
-                            0b0100_000000 | curr_sq,
-                            dest_sq,
-                        ));
-                        moves.push(ChessMove::from_curr_and_dest(
-                            0b1000_000000 | curr_sq,
-                            dest_sq,
-                        ));
-                    } else {
-                        moves.push(ChessMove::from_curr_and_dest(curr_sq, dest_sq))
+    /// Generates only legal moves, without trying them on a copy of the board. <br>
+    /// The pieces giving check and the pinned pieces are calculated once,
+    /// every piece may then only move to squares resolving the check and staying on the line of its pin.
+    fn legal_moves(&self, captures_only: bool) -> MoveList {
+        let mut moves = MoveList::new();
+        let color = self.side_to_move();
+        let ours = |role: Role| self.get_bitboard(Piece::new(role, color).into());
+        let theirs = |role: Role| self.get_bitboard(Piece::new(role, !color).into()).to_u64();
+        let us = self.get_pieces(color).to_u64();
+        let them = self.get_pieces(!color).to_u64();
+        let occupied = us | them;
+        // capturing moves only have to consider squares of enemy pieces
+        let dest_filter = if captures_only { them } else { !us };
+
+        let king_sq = ours(Role::King).into_iter().next();
+        let mut checkers = 0;
+        let mut check_mask = u64::MAX;
+        // squares a piece may move to without exposing the king, indexed by square
+        let mut pin_rays = [u64::MAX; 64];
+        let diagonal_sliders = theirs(Role::Bishop) | theirs(Role::Queen);
+        let straight_sliders = theirs(Role::Rook) | theirs(Role::Queen);
+
+        if let Some(king_sq) = king_sq {
+            checkers = self.attackers_of(king_sq, !color).to_u64();
+            check_mask = match checkers.count_ones() {
+                0 => u64::MAX,
+                // capturing the checking piece or blocking a slider
+                1 => checkers | between(king_sq, first_square(checkers)),
+                // only the king can escape a double check
+                _ => 0,
+            };
+
+            // sliders that would attack the king if only enemy pieces were on the board
+            let snipers = (magic::bishop_attacks(king_sq, Bitboard::new(them)).to_u64()
+                & diagonal_sliders)
+                | (magic::rook_attacks(king_sq, Bitboard::new(them)).to_u64() & straight_sliders);
+            for sniper_sq in Bitboard::new(snipers) {
+                let ray = between(king_sq, sniper_sq);
+                let blockers = ray & occupied;
+                if blockers.count_ones() == 1 && blockers & us != 0 {
+                    pin_rays[first_square(blockers) as usize] = ray | square_mask(sniper_sq);
+                }
+            }
+
+            // the king must not step on an attacked square, also not along the ray of a slider checking it
+            let mut without_king = *self;
+            without_king.remove_piece(Piece::new(Role::King, color).into(), king_sq);
+            let attacked = without_king.calc_attackmask(!color).to_u64();
+            let king_targets = ChessBoard::calc_king_attackmask(king_sq).to_u64() & dest_filter;
+            for dest_sq in Bitboard::new(king_targets & !attacked) {
+                moves.push(ChessMove::from_curr_and_dest(king_sq, dest_sq));
+            }
+
+            if !captures_only && checkers == 0 {
+                for (right, mv, empty, safe) in CASTLING_PATHS {
+                    if self.get_castling_rights() & castling::rights_of(color) & right != 0
+                        && occupied & empty == 0
+                        && attacked & safe == 0
+                    {
+                        moves.push(mv);
                     }
                 }
             }
         }
-        moves
-    }
-
-    pub fn w_bishop_moves(&self) -> Vec<ChessMove> {
-        let mut moves: Vec<ChessMove> = Vec::new();
-        let w_pieces = self.get_w_pieces();
-        let w_bishops = self.get_bitboard(Piece::WhiteBishop.into());
-
-        for curr_sq in w_bishops {
-            for dest_sq in self.calc_bishop_attackmask(curr_sq) {
-                if !w_pieces.get_bit(dest_sq) {
-                    moves.push(ChessMove::from_curr_and_dest(curr_sq, dest_sq));
-                }
-            }
-        }
-        moves
-    }
-
-    pub fn b_bishop_moves(&self) -> Vec<ChessMove> {
-        let mut moves: Vec<ChessMove> = Vec::new();
-        let b_pieces = self.get_b_pieces();
-        let b_bishops = self.get_bitboard(Piece::BlackBishop.into());
-
-        for curr_sq in b_bishops {
-            for dest_sq in self.calc_bishop_attackmask(curr_sq) {
-                if !b_pieces.get_bit(dest_sq) {
-                    moves.push(ChessMove::from_curr_and_dest(curr_sq, dest_sq));
-                }
-            }
-        }
-        moves
-    }
-
-    pub fn w_knight_moves(&self) -> Vec<ChessMove> {
-        let mut moves: Vec<ChessMove> = Vec::new();
-        let w_pieces = self.get_w_pieces();
-        let w_knights = self.get_bitboard(Piece::WhiteKnight.into());
-
-        for curr_sq in w_knights {
-            for dest_sq in ChessBoard::calc_knight_attackmask(curr_sq) {
-                if !w_pieces.get_bit(dest_sq) {
-                    moves.push(ChessMove::from_curr_and_dest(curr_sq, dest_sq));
-                }
-            }
-        }
-        moves
-    }
-
-    pub fn b_knight_moves(&self) -> Vec<ChessMove> {
-        let mut moves: Vec<ChessMove> = Vec::new();
-        let b_pieces = self.get_b_pieces();
-        let b_knights = self.get_bitboard(Piece::BlackKnight.into());
-
-        for curr_sq in b_knights {
-            for dest_sq in ChessBoard::calc_knight_attackmask(curr_sq) {
-                if !b_pieces.get_bit(dest_sq) {
-                    moves.push(ChessMove::from_curr_and_dest(curr_sq, dest_sq));
+        let targets = |sq: u16| dest_filter & check_mask & pin_rays[sq as usize];
+
+        let promotion_rank = match color {
+            Color::White => 0,
+            Color::Black => 7,
+        };
+        for curr_sq in ours(Role::Pawn) {
+            let attackmask = ChessBoard::calc_pawn_attackmask(curr_sq, color);
+            let movemask = self.calc_pawn_movemask(curr_sq, color);
+            for dest_sq in Bitboard::new(attackmask.to_u64() & them & targets(curr_sq)) {
+                push_pawn_move(&mut moves, curr_sq, dest_sq, promotion_rank);
+            }
+            for dest_sq in
+                Bitboard::new(movemask.to_u64() & check_mask & pin_rays[curr_sq as usize])
+            {
+                // promotions count as captures for the quiescence search
+                if !captures_only || dest_sq >> 3 == promotion_rank {
+                    push_pawn_move(&mut moves, curr_sq, dest_sq, promotion_rank);
+                }
+            }
+
+            let ep_sq = self.get_en_passant();
+            if ep_sq < 64 && attackmask.get_bit(ep_sq) {
+                // the captured pawn is next to the destination square, on the rank the pawn moves from
+                let captured_sq = (curr_sq & !0b111) | (ep_sq & 0b111);
+                // both pawns leave the rank at once, which could uncover an attack on the king
+                let occupied_after = (occupied & !square_mask(curr_sq) & !square_mask(captured_sq))
+                    | square_mask(ep_sq);
+                let legal = king_sq.is_none_or(|king_sq| {
+                    let sliders = (magic::bishop_attacks(king_sq, Bitboard::new(occupied_after))
+                        .to_u64()
+                        & diagonal_sliders)
+                        | (magic::rook_attacks(king_sq, Bitboard::new(occupied_after)).to_u64()
+                            & straight_sliders);
+                    // checks of knights and pawns can only be resolved by capturing the checking pawn
+                    let leapers = checkers
+                        & !square_mask(captured_sq)
+                        & !(diagonal_sliders | straight_sliders);
+                    sliders == 0 && leapers == 0
+                });
+                if legal {
+                    moves.push(ChessMove::from_curr_and_dest(curr_sq, ep_sq));
                 }
             }
         }
-        moves
-    }
-
-    pub fn w_rook_moves(&self) -> Vec<ChessMove> {
-        let mut moves: Vec<ChessMove> = Vec::new();
-        let w_pieces = self.get_w_pieces();
-        let w_rooks = self.get_bitboard(Piece::WhiteRook.into());
-
-        for curr_sq in w_rooks {
-            for dest_sq in self.calc_rook_attackmask(curr_sq) {
-                if !w_pieces.get_bit(dest_sq) {
-                    moves.push(ChessMove::from_curr_and_dest(curr_sq, dest_sq));
-                }
+        for curr_sq in ours(Role::Bishop) {
+            for dest_sq in
+                Bitboard::new(self.calc_bishop_attackmask(curr_sq).to_u64() & targets(curr_sq))
+            {
+                moves.push(ChessMove::from_curr_and_dest(curr_sq, dest_sq));
             }
         }
-        moves
-    }
-
-    pub fn b_rook_moves(&self) -> Vec<ChessMove> {
-        let mut moves: Vec<ChessMove> = Vec::new();
-        let b_pieces = self.get_b_pieces();
-        let b_rooks = self.get_bitboard(Piece::BlackRook.into());
-
-        for curr_sq in b_rooks {
-            for dest_sq in self.calc_rook_attackmask(curr_sq) {
-                if !b_pieces.get_bit(dest_sq) {
-                    moves.push(ChessMove::from_curr_and_dest(curr_sq, dest_sq));
-                }
+        for curr_sq in ours(Role::Knight) {
+            for dest_sq in Bitboard::new(
+                ChessBoard::calc_knight_attackmask(curr_sq).to_u64() & targets(curr_sq),
+            ) {
+                moves.push(ChessMove::from_curr_and_dest(curr_sq, dest_sq));
             }
         }
-        moves
-    }
-
-    pub fn w_king_moves(&self) -> Vec<ChessMove> {
-        let mut moves: Vec<ChessMove> = Vec::new();
-        let w_pieces = self.get_w_pieces();
-        let w_king = self.get_bitboard(Piece::WhiteKing.into());
-        let b_attackmask = self.calc_b_attackmask();
-
-        for curr_sq in w_king {
-            for dest_sq in ChessBoard::calc_king_attackmask(curr_sq) {
-                if !w_pieces.get_bit(dest_sq) && !b_attackmask.get_bit(dest_sq) {
-                    moves.push(ChessMove::from_curr_and_dest(curr_sq, dest_sq));
-                }
-            }
-            //castling
-            if self.get_castling_rights() & castling::WHITE_K != 0
-                && !self.castling_blocked(castling::WHITE_K)
+        for curr_sq in ours(Role::Rook) {
+            for dest_sq in
+                Bitboard::new(self.calc_rook_attackmask(curr_sq).to_u64() & targets(curr_sq))
             {
-                moves.push(CastleMove::WHITE_K)
-            }
-            if self.get_castling_rights() & castling::WHITE_Q != 0
-                && !self.castling_blocked(castling::WHITE_Q)
-            {
-                moves.push(CastleMove::WHITE_Q)
+                moves.push(ChessMove::from_curr_and_dest(curr_sq, dest_sq));
             }
         }
-        moves
-    }
-
-    pub fn b_king_moves(&self) -> Vec<ChessMove> {
-        let mut moves: Vec<ChessMove> = Vec::new();
-        let b_pieces = self.get_b_pieces();
-        let b_king = self.get_bitboard(Piece::BlackKing.into());
-        let w_attackmask = self.calc_w_attackmask();
-
-        for curr_sq in b_king {
-            for dest_sq in ChessBoard::calc_king_attackmask(curr_sq) {
-                if !b_pieces.get_bit(dest_sq) && !w_attackmask.get_bit(dest_sq) {
-                    moves.push(ChessMove::from_curr_and_dest(curr_sq, dest_sq));
-                }
-            }
-            //castling
-            if self.get_castling_rights() & castling::BLACK_K != 0
-                && !self.castling_blocked(castling::BLACK_K)
-            {
-                moves.push(CastleMove::BLACK_K)
-            }
-            if self.get_castling_rights() & castling::BLACK_Q != 0
-                && !self.castling_blocked(castling::BLACK_Q)
+        for curr_sq in ours(Role::Queen) {
+            for dest_sq in
+                Bitboard::new(self.calc_queen_attackmask(curr_sq).to_u64() & targets(curr_sq))
             {
-                moves.push(CastleMove::BLACK_Q)
+                moves.push(ChessMove::from_curr_and_dest(curr_sq, dest_sq));
             }
         }
         moves
     }
+}
 
-    pub fn w_queen_moves(&self) -> Vec<ChessMove> {
-        let mut moves: Vec<ChessMove> = Vec::new();
-        let w_pieces = self.get_w_pieces();
-        let w_queens = self.get_bitboard(Piece::WhiteQueen.into());
-
-        for curr_sq in w_queens {
-            for dest_sq in self.calc_queen_attackmask(curr_sq) {
-                if !w_pieces.get_bit(dest_sq) {
-                    moves.push(ChessMove::from_curr_and_dest(curr_sq, dest_sq));
-                }
-            }
-        }
-        moves
-    }
+/// Mask of the square, index 0 (a8) is the most significant bit
+fn square_mask(sq_index: u16) -> u64 {
+    1 << (63 - sq_index)
+}
 
-    pub fn b_queen_moves(&self) -> Vec<ChessMove> {
-        let mut moves: Vec<ChessMove> = Vec::new();
-        let b_pieces = self.get_b_pieces();
-        let b_queens = self.get_bitboard(Piece::BlackQueen.into());
+/// Index of the square of the most significant set bit
+fn first_square(mask: u64) -> u16 {
+    mask.leading_zeros() as u16
+}
 
-        for curr_sq in b_queens {
-            for dest_sq in self.calc_queen_attackmask(curr_sq) {
-                if !b_pieces.get_bit(dest_sq) {
-                    moves.push(ChessMove::from_curr_and_dest(curr_sq, dest_sq));
-                }
-            }
+/// Squares strictly between two squares on the same rank, file or diagonal, empty if they aren't on one line
+fn between(a: u16, b: u16) -> u64 {
+    let (a_mask, b_mask) = (Bitboard::new(square_mask(a)), Bitboard::new(square_mask(b)));
+    for attacks in [magic::rook_attacks, magic::bishop_attacks] {
+        if attacks(a, b_mask).get_bit(b) {
+            return (attacks(a, b_mask) & attacks(b, a_mask)).to_u64();
         }
-        moves
     }
+    0
+}
 
-    /// Checking if castling on a side is possible. <br>
-    /// The following parameters are considered:
-    /// - Is King in check?
-    /// - Is the way blocked by a piece?
-    /// - Does the player still have castling rights for the side?
-    /// - Is the way attacked by an enemy piece?
-    pub fn castling_blocked(&self, side: u8) -> bool {
-        let all_pieces = self.get_all_pieces();
-        let w_attackmask = self.calc_w_attackmask();
-        let b_attackmask = self.calc_b_attackmask();
-        // mask for checking the indexes for white castling
-        let mask_white = all_pieces | b_attackmask;
-        let mask_black = all_pieces | w_attackmask;
-        match side {
-            castling::WHITE_K => {
-                (self.get_bitboard(Piece::WhiteKing.into()) & b_attackmask).to_u64() != 0
-                    || self.get_castling_rights() & castling::WHITE_K == 0
-                    || mask_white.get_bit(61u16)
-                    || mask_white.get_bit(62u16)
-            }
-            castling::WHITE_Q => {
-                (self.get_bitboard(Piece::WhiteKing.into()) & b_attackmask).to_u64() != 0
-                    || self.get_castling_rights() & castling::WHITE_Q == 0
-                    || mask_white.get_bit(59u16)
-                    || mask_white.get_bit(58u16)
-                    || mask_white.get_bit(57u16)
-            }
-            castling::BLACK_K => {
-                (self.get_bitboard(Piece::BlackKing.into()) & w_attackmask).to_u64() != 0
-                    || self.get_castling_rights() & castling::BLACK_K == 0
-                    || mask_black.get_bit(5u16)
-                    || mask_black.get_bit(6u16)
-            }
-            castling::BLACK_Q => {
-                (self.get_bitboard(Piece::BlackKing.into()) & w_attackmask).to_u64() != 0
-                    || self.get_castling_rights() & castling::BLACK_Q == 0
-                    || mask_black.get_bit(3u16)
-                    || mask_black.get_bit(2u16)
-                    || mask_black.get_bit(1u16)
-            }
-            _ => panic!("Wrong Castling configuration provided!"),
+/// Pushes a pawn move, or all four promotions if the pawn reaches the last rank
+fn push_pawn_move(moves: &mut MoveList, curr_sq: u16, dest_sq: u16, promotion_rank: u16) {
+    if dest_sq >> 3 == promotion_rank {
+        // Pawn Promotion, see encoding in ChessMove docu
+        for encoding in [0b0001_000000, 0b0010_000000, 0b0100_000000, 0b1000_000000] {
+            moves.push(ChessMove::from_curr_and_dest(encoding | curr_sq, dest_sq));
         }
+    } else {
+        moves.push(ChessMove::from_curr_and_dest(curr_sq, dest_sq));
     }
 }
@@ -1,141 +1,82 @@
 use crate::{
+    core::{
+        attacktables::{KING_ATTACKS, KNIGHT_ATTACKS, PAWN_ATTACKS},
+        magic,
+    },
     interface::abs_diff_u16,
     models::{
         board::{Bitboard, ChessBoard},
-        piece::Piece,
+        piece::{Color, Piece, Role},
     },
 };
 
 impl ChessBoard {
-    /// Calculates the attackmask for every piece white piece. <br>
+    /// Calculates the attackmask for every piece of a color. <br>
     /// The attackmask shows, which squares a piece could capture, if a piece of the enemy color was on it.
-    pub fn calc_w_attackmask(&self) -> Bitboard {
-        let mut attackmask = Bitboard::new(0);
-
-        let w_pawns = self.get_bitboard(Piece::WhitePawn as usize);
-        let w_bishops = self.get_bitboard(Piece::WhiteBishop as usize);
-        let w_knights = self.get_bitboard(Piece::WhiteKnight as usize);
-        let w_rooks = self.get_bitboard(Piece::WhiteRook as usize);
-        let w_queens = self.get_bitboard(Piece::WhiteQueen as usize);
-        let w_king = self.get_bitboard(Piece::WhiteKing as usize);
-
-        for sq_index in w_pawns {
-            attackmask |= ChessBoard::calc_w_pawn_attackmask(sq_index);
-        }
-        for sq_index in w_bishops {
-            attackmask |= self.calc_bishop_attackmask(sq_index);
-        }
-        for sq_index in w_knights {
-            attackmask |= ChessBoard::calc_knight_attackmask(sq_index);
-        }
-        for sq_index in w_rooks {
-            attackmask |= self.calc_rook_attackmask(sq_index);
-        }
-        for sq_index in w_queens {
-            attackmask |= self.calc_queen_attackmask(sq_index);
-        }
-        for sq_index in w_king {
-            attackmask |= ChessBoard::calc_king_attackmask(sq_index);
-        }
-
-        attackmask
-    }
-
-    /// Calculates the attackmask for every black piece
-    pub fn calc_b_attackmask(&self) -> Bitboard {
+    pub fn calc_attackmask(&self, color: Color) -> Bitboard {
         let mut attackmask = Bitboard::new(0);
+        let pieces = |role: Role| self.get_bitboard(Piece::new(role, color).into());
 
-        let b_pawns = self.get_bitboard(Piece::BlackPawn as usize);
-        let b_bishops = self.get_bitboard(Piece::BlackBishop as usize);
-        let b_knights = self.get_bitboard(Piece::BlackKnight as usize);
-        let b_rooks = self.get_bitboard(Piece::BlackRook as usize);
-        let b_queens = self.get_bitboard(Piece::BlackQueen as usize);
-        let b_king = self.get_bitboard(Piece::BlackKing as usize);
-
-        for sq_index in b_pawns {
-            attackmask |= ChessBoard::calc_b_pawn_attackmask(sq_index);
+        for sq_index in pieces(Role::Pawn) {
+            attackmask |= ChessBoard::calc_pawn_attackmask(sq_index, color);
         }
-        for sq_index in b_bishops {
+        for sq_index in pieces(Role::Bishop) {
             attackmask |= self.calc_bishop_attackmask(sq_index);
         }
-        for sq_index in b_knights {
+        for sq_index in pieces(Role::Knight) {
             attackmask |= ChessBoard::calc_knight_attackmask(sq_index);
         }
-        for sq_index in b_rooks {
+        for sq_index in pieces(Role::Rook) {
             attackmask |= self.calc_rook_attackmask(sq_index);
         }
-        for sq_index in b_queens {
+        for sq_index in pieces(Role::Queen) {
             attackmask |= self.calc_queen_attackmask(sq_index);
         }
-        for sq_index in b_king {
+        for sq_index in pieces(Role::King) {
             attackmask |= ChessBoard::calc_king_attackmask(sq_index);
         }
 
         attackmask
     }
-    pub fn calc_w_pawn_movemask(&self, sq_index: u16) -> Bitboard {
-        let mut movemask = Bitboard::new(0);
-        let all_pieces = self.get_all_pieces();
-        if !all_pieces.get_bit(sq_index - 8) {
-            movemask.set_bit(sq_index - 8);
-            if sq_index >> 3 == 6 && !all_pieces.get_bit(sq_index - 16) {
-                movemask.set_bit(sq_index - 16);
-            }
-        }
-        movemask
-    }
 
-    pub fn calc_w_pawn_attackmask(sq_index: u16) -> Bitboard {
-        let mut attackmask = Bitboard::new(0);
-
-        match sq_index & 0b111 {
-            0 => {
-                attackmask.set_bit(sq_index - 7);
-            }
-            7 => {
-                attackmask.set_bit(sq_index - 9);
-            }
-            _ => {
-                attackmask.set_bit(sq_index - 7);
-                attackmask.set_bit(sq_index - 9);
-            }
-        }
-        attackmask
+    /// Checks if the king of the side to move is attacked
+    pub fn is_in_check(&self) -> bool {
+        let color = self.side_to_move();
+        (self.get_bitboard(Piece::new(Role::King, color).into()) & self.calc_attackmask(!color))
+            .to_u64()
+            != 0
     }
 
-    pub fn calc_b_pawn_movemask(&self, sq_index: u16) -> Bitboard {
+    /// Calculates the squares a pawn can push to, one square or two from its starting rank
+    pub fn calc_pawn_movemask(&self, sq_index: u16, color: Color) -> Bitboard {
         let mut movemask = Bitboard::new(0);
         let all_pieces = self.get_all_pieces();
-        if !all_pieces.get_bit(sq_index + 8) {
-            movemask.set_bit(sq_index + 8);
-            if sq_index >> 3 == 1 && !all_pieces.get_bit(sq_index + 16) {
-                movemask.set_bit(sq_index + 16);
+        let (forward, start_rank): (fn(u16, u16) -> u16, u16) = match color {
+            Color::White => (|sq, n| sq - n, 6),
+            Color::Black => (|sq, n| sq + n, 1),
+        };
+        if !all_pieces.get_bit(forward(sq_index, 8)) {
+            movemask.set_bit(forward(sq_index, 8));
+            if sq_index >> 3 == start_rank && !all_pieces.get_bit(forward(sq_index, 16)) {
+                movemask.set_bit(forward(sq_index, 16));
             }
         }
         movemask
     }
 
-    pub fn calc_b_pawn_attackmask(sq_index: u16) -> Bitboard {
-        let mut attackmask = Bitboard::new(0);
-
-        match sq_index & 0b111 {
-            0 => {
-                attackmask.set_bit(sq_index + 9);
-            }
-            7 => {
-                attackmask.set_bit(sq_index + 7);
-            }
-            _ => {
-                attackmask.set_bit(sq_index + 9);
-                attackmask.set_bit(sq_index + 7);
-            }
-        }
-        attackmask
+    pub fn calc_pawn_attackmask(sq_index: u16, color: Color) -> Bitboard {
+        PAWN_ATTACKS[color as usize][sq_index as usize]
     }
 
+    /// Looks up the attackmask of a bishop in the precomputed magic bitboard tables
     pub fn calc_bishop_attackmask(&self, sq_index: u16) -> Bitboard {
+        magic::bishop_attacks(sq_index, self.get_all_pieces())
+    }
+
+    /// Calculates the attackmask of a bishop by walking along its rays until a piece blocks it. <br>
+    /// Used to fill the magic bitboard tables.
+    pub fn calc_bishop_rays(sq_index: u16, all_pieces: Bitboard) -> Bitboard {
         let mut attackmask = Bitboard::new(0);
-        let all_pieces = self.get_all_pieces();
 
         if (sq_index & 0b111) != 7 {
             // moving to upper right
@@ -201,41 +142,18 @@ impl ChessBoard {
     }
 
     pub fn calc_knight_attackmask(sq_index: u16) -> Bitboard {
-        let mut attackmask = Bitboard::new(0);
-
-        let dest_index_offsets_pos: [u16; 4] = [6, 10, 15, 17];
-        for offset in dest_index_offsets_pos {
-            let dest_index = sq_index + offset;
-            // Checking if the move would lead below the board
-            if dest_index > 63 {
-                break;
-            }
-            // Checking if the move would lead over the border
-            if abs_diff_u16(dest_index & 0b111, sq_index & 0b111) > 2 {
-                continue;
-            }
-            attackmask.set_bit(dest_index);
-        }
-        let dest_index_offsets_neg: [i16; 4] = [-6, -10, -15, -17];
-        for offset in dest_index_offsets_neg {
-            let dest_index_i16 = sq_index as i16 + offset;
-            // Checking if the move would lead above the board
-            if dest_index_i16 < 0 {
-                break;
-            }
-            let dest_index = dest_index_i16 as u16;
-            // Checking if the move would lead over the border
-            if abs_diff_u16(dest_index & 0b111, sq_index & 0b111) > 2 {
-                continue;
-            }
-            attackmask.set_bit(dest_index);
-        }
-        attackmask
+        KNIGHT_ATTACKS[sq_index as usize]
     }
 
+    /// Looks up the attackmask of a rook in the precomputed magic bitboard tables
     pub fn calc_rook_attackmask(&self, sq_index: u16) -> Bitboard {
+        magic::rook_attacks(sq_index, self.get_all_pieces())
+    }
+
+    /// Calculates the attackmask of a rook by walking along its rays until a piece blocks it. <br>
+    /// Used to fill the magic bitboard tables.
+    pub fn calc_rook_rays(sq_index: u16, all_pieces: Bitboard) -> Bitboard {
         let mut attackmask = Bitboard::new(0);
-        let all_pieces = self.get_all_pieces();
 
         // Moving right
         // Upper boundary of for loop is the index of h file of the rank of the rook
@@ -279,10 +197,68 @@ impl ChessBoard {
     }
 
     pub fn calc_queen_attackmask(&self, sq_index: u16) -> Bitboard {
-        self.calc_bishop_attackmask(sq_index) | self.calc_rook_attackmask(sq_index)
+        magic::queen_attacks(sq_index, self.get_all_pieces())
     }
 
     pub fn calc_king_attackmask(sq_index: u16) -> Bitboard {
+        KING_ATTACKS[sq_index as usize]
+    }
+}
+
+// the offset calculations are no longer used by the engine, they are kept as a reference for testing the lookup tables
+#[allow(dead_code)]
+impl ChessBoard {
+    pub fn calc_pawn_attackmask_by_offsets(sq_index: u16, color: Color) -> Bitboard {
+        let mut attackmask = Bitboard::new(0);
+        // the diagonal towards the a file and the one towards the h file
+        let (left, right) = match color {
+            Color::White => (sq_index.wrapping_sub(9), sq_index.wrapping_sub(7)),
+            Color::Black => (sq_index + 7, sq_index + 9),
+        };
+
+        if sq_index & 0b111 != 0 {
+            attackmask.set_bit(left);
+        }
+        if sq_index & 0b111 != 7 {
+            attackmask.set_bit(right);
+        }
+        attackmask
+    }
+
+    pub fn calc_knight_attackmask_by_offsets(sq_index: u16) -> Bitboard {
+        let mut attackmask = Bitboard::new(0);
+
+        let dest_index_offsets_pos: [u16; 4] = [6, 10, 15, 17];
+        for offset in dest_index_offsets_pos {
+            let dest_index = sq_index + offset;
+            // Checking if the move would lead below the board
+            if dest_index > 63 {
+                break;
+            }
+            // Checking if the move would lead over the border
+            if abs_diff_u16(dest_index & 0b111, sq_index & 0b111) > 2 {
+                continue;
+            }
+            attackmask.set_bit(dest_index);
+        }
+        let dest_index_offsets_neg: [i16; 4] = [-6, -10, -15, -17];
+        for offset in dest_index_offsets_neg {
+            let dest_index_i16 = sq_index as i16 + offset;
+            // Checking if the move would lead above the board
+            if dest_index_i16 < 0 {
+                break;
+            }
+            let dest_index = dest_index_i16 as u16;
+            // Checking if the move would lead over the border
+            if abs_diff_u16(dest_index & 0b111, sq_index & 0b111) > 2 {
+                continue;
+            }
+            attackmask.set_bit(dest_index);
+        }
+        attackmask
+    }
+
+    pub fn calc_king_attackmask_by_offsets(sq_index: u16) -> Bitboard {
         let mut attackmask = Bitboard::new(0);
         let dest_index_offsets_pos: [u16; 4] = [1, 7, 8, 9];
         for offset in dest_index_offsets_pos {
@@ -0,0 +1,272 @@
+use crate::models::{
+    board::ChessBoard,
+    chessmove::ChessMove,
+    movelist::{MAX_MOVES, MoveList},
+    piece::{Piece, Role},
+};
+
+/// Upper limit of a history score, all scores get halved when it is reached
+const MAX_HISTORY: i32 = 1 << 20;
+
+/// Stages of the move picker, in the order the moves are returned
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Stage {
+    HashMove,
+    Captures,
+    Killers,
+    Quiets,
+    BadCaptures,
+    Done,
+}
+
+/// Scores of quiet moves that caused a beta cutoff, indexed by the moving piece and the destination square
+pub struct History([[i32; 64]; 12]);
+
+impl History {
+    pub fn new() -> Self {
+        Self([[0; 64]; 12])
+    }
+
+    pub fn get(&self, piece: Piece, mv: ChessMove) -> i32 {
+        self.0[usize::from(piece)][mv.get_dest_square_as_index() as usize]
+    }
+
+    /// Rewards a quiet move that caused a beta cutoff, deeper cutoffs are worth more
+    pub fn update(&mut self, piece: Piece, mv: ChessMove, depth: u8) {
+        let entry = &mut self.0[usize::from(piece)][mv.get_dest_square_as_index() as usize];
+        *entry += depth as i32 * depth as i32;
+        if *entry >= MAX_HISTORY {
+            for score in self.0.iter_mut().flatten() {
+                *score /= 2;
+            }
+        }
+    }
+}
+
+impl Default for History {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// A move with the score it gets ordered by
+#[derive(Debug, Clone, Copy)]
+struct ScoredMove {
+    mv: ChessMove,
+    score: i32,
+}
+
+/// Moves with their scores, stored on the stack like a `MoveList`
+struct ScoredList {
+    moves: [ScoredMove; MAX_MOVES],
+    len: usize,
+}
+
+impl ScoredList {
+    fn new() -> Self {
+        Self {
+            moves: [ScoredMove {
+                mv: ChessMove::new(0),
+                score: 0,
+            }; MAX_MOVES],
+            len: 0,
+        }
+    }
+
+    fn push(&mut self, mv: ChessMove) {
+        self.moves[self.len] = ScoredMove { mv, score: 0 };
+        self.len += 1;
+    }
+
+    fn contains(&self, mv: ChessMove) -> bool {
+        self.moves[..self.len].iter().any(|entry| entry.mv == mv)
+    }
+
+    /// Computes the score of every move once
+    fn score(&mut self, score: impl Fn(ChessMove) -> i32) {
+        for entry in &mut self.moves[..self.len] {
+            entry.score = score(entry.mv);
+        }
+    }
+
+    /// Moves the best scored move from `index` on to `index` and returns it,
+    /// a beta cutoff after the first few moves saves sorting the rest
+    fn pick_best(&mut self, index: usize) -> Option<ChessMove> {
+        let remaining = self.moves.get(index..self.len)?;
+        let best = index + (0..remaining.len()).max_by_key(|&i| remaining[i].score)?;
+        self.moves.swap(index, best);
+        Some(self.moves[index].mv)
+    }
+}
+
+/// Returns the legal moves of a position one by one, the most promising first:
+/// the hash move, winning captures ordered by MVV-LVA, the killer moves, the quiet moves ordered by their history score
+/// and at last the captures losing material according to the static exchange evaluation. <br>
+/// The moves are generated up front, but a group only gets scored once it is reached
+/// and the best remaining move is picked one at a time, so a beta cutoff saves the remaining work.
+pub struct MovePicker {
+    board: ChessBoard,
+    stage: Stage,
+    hash_mv: Option<ChessMove>,
+    killers: [Option<ChessMove>; 2],
+    /// Position of the next move in the list of the current stage
+    index: usize,
+    captures: ScoredList,
+    quiets: ScoredList,
+    /// Captures that failed the exchange check, already in MVV-LVA order
+    bad_captures: MoveList,
+    /// Drops the losing captures instead of returning them last
+    skip_bad_captures: bool,
+}
+
+impl MovePicker {
+    /// Creates a picker over the legal moves `moves` of `board`
+    pub fn new(
+        board: &ChessBoard,
+        moves: MoveList,
+        hash_mv: Option<ChessMove>,
+        killers: [Option<ChessMove>; 2],
+    ) -> Self {
+        let mut captures = ScoredList::new();
+        let mut quiets = ScoredList::new();
+        for &mv in &moves {
+            if is_capture(board, mv) {
+                captures.push(mv);
+            } else {
+                quiets.push(mv);
+            }
+        }
+        Self {
+            board: *board,
+            stage: Stage::HashMove,
+            // the hash move only gets returned if it is legal in this position
+            hash_mv: hash_mv.filter(|hash_mv| moves.contains(hash_mv)),
+            killers,
+            index: 0,
+            captures,
+            quiets,
+            bad_captures: MoveList::new(),
+            skip_bad_captures: false,
+        }
+    }
+
+    /// Creates a picker over captures and promotions for the quiescence search,
+    /// with `skip_bad_captures` the captures losing material are never returned
+    pub fn captures(board: &ChessBoard, moves: MoveList, skip_bad_captures: bool) -> Self {
+        Self {
+            skip_bad_captures,
+            ..Self::new(board, moves, None, [None; 2])
+        }
+    }
+
+    /// Returns the next move, `None` after every move was returned
+    pub fn next(&mut self, history: &History) -> Option<ChessMove> {
+        let board = self.board;
+        loop {
+            match self.stage {
+                Stage::HashMove => {
+                    self.next_stage(Stage::Captures);
+                    self.captures.score(|mv| mvv_lva(&board, mv));
+                    if self.hash_mv.is_some() {
+                        return self.hash_mv;
+                    }
+                }
+                Stage::Captures => {
+                    let Some(mv) = self.captures.pick_best(self.index) else {
+                        self.next_stage(Stage::Killers);
+                        continue;
+                    };
+                    self.index += 1;
+                    if Some(mv) == self.hash_mv {
+                        continue;
+                    }
+                    // the exchange is only evaluated once, a losing capture keeps its place in the list
+                    if !board.see_ge(mv, 0) {
+                        if !self.skip_bad_captures {
+                            self.bad_captures.push(mv);
+                        }
+                        continue;
+                    }
+                    return Some(mv);
+                }
+                Stage::Killers => {
+                    let Some(&killer) = self.killers.get(self.index) else {
+                        self.next_stage(Stage::Quiets);
+                        self.quiets.score(|mv| {
+                            board
+                                .piece_on(mv.get_curr_square_as_index())
+                                .map_or(0, |piece| history.get(piece, mv))
+                        });
+                        continue;
+                    };
+                    self.index += 1;
+                    // a killer of a sibling position doesn't have to be legal here
+                    if let Some(killer) = killer
+                        && Some(killer) != self.hash_mv
+                        && self.quiets.contains(killer)
+                    {
+                        return Some(killer);
+                    }
+                }
+                Stage::Quiets => {
+                    let Some(mv) = self.quiets.pick_best(self.index) else {
+                        self.next_stage(Stage::BadCaptures);
+                        continue;
+                    };
+                    self.index += 1;
+                    if Some(mv) != self.hash_mv && !self.killers.contains(&Some(mv)) {
+                        return Some(mv);
+                    }
+                }
+                Stage::BadCaptures => {
+                    let Some(&mv) = self.bad_captures.get(self.index) else {
+                        self.next_stage(Stage::Done);
+                        continue;
+                    };
+                    self.index += 1;
+                    return Some(mv);
+                }
+                Stage::Done => return None,
+            }
+        }
+    }
+
+    fn next_stage(&mut self, stage: Stage) {
+        self.stage = stage;
+        self.index = 0;
+    }
+}
+
+/// Checks if the move captures a piece or promotes a pawn
+pub fn is_capture(board: &ChessBoard, mv: ChessMove) -> bool {
+    let dest = mv.get_dest_square_as_index();
+    if mv.promotion().is_some() || board.piece_on(dest).is_some() {
+        return true;
+    }
+    // en passant
+    dest == board.get_en_passant()
+        && matches!(
+            board
+                .piece_on(mv.get_curr_square_as_index())
+                .map(Piece::role),
+            Some(Role::Pawn)
+        )
+}
+
+/// Most valuable victim - least valuable attacker, the value of the captured piece decides first. <br>
+/// The king as an attacker is counted like a queen, a promotion adds the value of the new piece.
+fn mvv_lva(board: &ChessBoard, mv: ChessMove) -> i32 {
+    let victim = board
+        .piece_on(mv.get_dest_square_as_index())
+        .map_or(0, Piece::value);
+    let attacker = board
+        .piece_on(mv.get_curr_square_as_index())
+        .map_or(0, |piece| piece.value().min(Role::Queen.value()));
+    let promotion = match mv.promotion() {
+        Some(role) => role.value(),
+        // an en passant capture has no piece on the destination square
+        None if victim == 0 => Role::Pawn.value(),
+        None => 0,
+    };
+    (victim + promotion) * 10 - attacker
+}
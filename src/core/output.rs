@@ -0,0 +1,21 @@
+use std::fmt::Display;
+use std::io::Write;
+use std::sync::{Arc, Mutex};
+
+/// Destination of the lines sent to the GUI, shared by the UCI loop and the search threads. <br>
+/// Every line is written at once and flushed, so lines of different threads don't get mixed up.
+#[derive(Clone)]
+pub struct Output(Arc<Mutex<dyn Write + Send>>);
+
+impl Output {
+    pub fn new(writer: impl Write + Send + 'static) -> Self {
+        Self(Arc::new(Mutex::new(writer)))
+    }
+
+    /// Writes the line, errors are ignored since there is nobody left to report them to
+    pub fn line(&self, line: impl Display) {
+        let mut writer = self.0.lock().expect("Output lock poisoned");
+        let _ = writeln!(writer, "{}", line);
+        let _ = writer.flush();
+    }
+}
@@ -0,0 +1,88 @@
+use crate::core::alphabeta::MATE_SCORE;
+use crate::core::movepick::MovePicker;
+use crate::core::search::{MAX_PLY, Search};
+use crate::models::{
+    board::ChessBoard,
+    chessmove::ChessMove,
+    piece::{Piece, Role},
+};
+
+/// Safety margin of the delta pruning, covers the positional gain of a capture
+const DELTA_MARGIN: i32 = 200;
+
+impl Search {
+    /// Extends the search at the horizon until the position is quiet, so no capture sequence gets cut off. <br>
+    /// Searches captures and promotions, or every evasion when in check.
+    pub fn quiescence(
+        &mut self,
+        board: &mut ChessBoard,
+        ply: u16,
+        mut alpha: i32,
+        beta: i32,
+    ) -> i32 {
+        self.nodes += 1;
+        if self.nodes & 1023 == 0 {
+            self.check_limits();
+        }
+        if self.stopped {
+            return 0;
+        }
+        self.seldepth = self.seldepth.max(ply);
+        self.pv_table[ply as usize].clear();
+        if ply as usize >= MAX_PLY {
+            return board.evaluate_relative();
+        }
+
+        let in_check = board.is_in_check();
+        let mut best_eval = -MATE_SCORE + ply as i32;
+        let stand_pat = board.evaluate_relative();
+        let mut picker = if in_check {
+            MovePicker::new(board, board.all_possible_moves(), None, [None; 2])
+        } else {
+            // standing pat, the side to move doesn't have to capture
+            if stand_pat >= beta {
+                return stand_pat;
+            }
+            alpha = alpha.max(stand_pat);
+            best_eval = stand_pat;
+            // captures losing material are pruned, the opponent would just win the exchange
+            MovePicker::captures(
+                board,
+                board.capture_moves(),
+                self.selectivity.qsearch_pruning,
+            )
+        };
+
+        let prune = !in_check && self.selectivity.qsearch_pruning;
+        while let Some(mv) = picker.next(&self.history) {
+            // delta pruning, skipping captures that can't raise alpha even with a big positional gain
+            if prune
+                && mv.promotion().is_none()
+                && stand_pat + captured_value(board, mv) + DELTA_MARGIN <= alpha
+            {
+                continue;
+            }
+            let undo = board.make_move(mv);
+            let eval = -self.quiescence(board, ply + 1, -beta, -alpha);
+            board.unmake_move(mv, undo);
+            if eval > best_eval {
+                best_eval = eval;
+                if eval > alpha {
+                    alpha = eval;
+                    self.update_pv(ply as usize, mv);
+                    if alpha >= beta {
+                        break;
+                    }
+                }
+            }
+        }
+        best_eval
+    }
+}
+
+/// Value of the piece captured by the move, a move to an empty square is an en passant capture
+fn captured_value(board: &ChessBoard, mv: ChessMove) -> i32 {
+    board
+        .piece_on(mv.get_dest_square_as_index())
+        .map_or(Role::Pawn.value(), Piece::value)
+}
@@ -0,0 +1,312 @@
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::time::{Duration, Instant};
+
+use crate::core::alphabeta::{INFINITY, MATE_SCORE};
+use crate::core::bestmv::PvLine;
+use crate::core::info::{MATE_BOUND, SearchInfo};
+use crate::core::limits::{GoParams, TimeBudget};
+use crate::core::movepick::History;
+use crate::core::output::Output;
+use crate::core::selectivity::Selectivity;
+use crate::core::tt::{Bound, DEFAULT_HASH_MB, TranspositionTable};
+use crate::models::{board::ChessBoard, chessmove::ChessMove};
+
+/// Maximum depth of the iterative deepening
+pub const MAX_DEPTH: u8 = 64;
+/// Maximum distance to the root, the size of the principal variation table
+pub const MAX_PLY: usize = 128;
+/// Half width of the first aspiration window around the score of the previous iteration
+const ASPIRATION_DELTA: i32 = 25;
+/// First depth searched with an aspiration window, the scores of shallower iterations are too unstable
+const ASPIRATION_MIN_DEPTH: u8 = 4;
+
+/// Flags for controlling a running search from another thread
+#[derive(Debug, Default)]
+pub struct SearchSignals {
+    /// Aborts the search as soon as possible
+    pub stop: AtomicBool,
+    /// Set while the engine ponders, the time limits only apply after it got cleared by `ponderhit`
+    pub ponder: AtomicBool,
+}
+
+/// State of a single search, driven by iterative deepening within the limits of a `go` command
+pub struct Search {
+    board: ChessBoard,
+    params: GoParams,
+    start: Instant,
+    budget: Option<TimeBudget>,
+    signals: Arc<SearchSignals>,
+    pub(crate) tt: Arc<TranspositionTable>,
+    pondering: bool,
+    /// Destination of the `info` lines, a search without one reports nothing
+    output: Option<Output>,
+    /// Helper thread of a multithreaded search, see `SearchThread`
+    helper: bool,
+    /// Number of best lines searched with an exact score, the UCI `MultiPV` option
+    pub(crate) multi_pv: usize,
+    /// Pruning, reductions and extensions enabled with the UCI options
+    pub(crate) selectivity: Selectivity,
+    pub(crate) nodes: u64,
+    /// Nodes of the helper threads, each helper adds its nodes in steps of 1024
+    helper_nodes: Arc<AtomicU64>,
+    pub(crate) seldepth: u16,
+    /// Depth of the current iteration, checks are only extended up to twice as many plies
+    pub(crate) root_depth: u8,
+    pub(crate) stopped: bool,
+    /// Triangular principal variation table, `pv_table[ply]` holds the best line found from that ply
+    pub(crate) pv_table: Vec<Vec<ChessMove>>,
+    /// Two quiet moves per ply that caused a beta cutoff in a sibling position
+    pub(crate) killers: Vec<[Option<ChessMove>; 2]>,
+    pub(crate) history: History,
+    /// Set at the plies reached by a null move, so no two null moves follow each other
+    pub(crate) null_moves: Vec<bool>,
+    /// Best lines of the last completed iteration, ranked by score
+    lines: Vec<PvLine>,
+}
+
+impl Search {
+    /// Search with a transposition table of its own
+    pub fn new(board: ChessBoard, params: GoParams) -> Self {
+        Self::with_tt(
+            board,
+            params,
+            Arc::new(TranspositionTable::new(DEFAULT_HASH_MB)),
+        )
+    }
+
+    /// Search sharing the transposition table with other searches, those before it and those running beside it
+    pub fn with_tt(board: ChessBoard, params: GoParams, tt: Arc<TranspositionTable>) -> Self {
+        Self {
+            board,
+            params,
+            start: Instant::now(),
+            budget: params.time_budget(board.get_white_to_move()),
+            signals: Arc::new(SearchSignals::default()),
+            tt,
+            pondering: params.ponder,
+            output: None,
+            helper: false,
+            multi_pv: 1,
+            selectivity: Selectivity::default(),
+            nodes: 0,
+            helper_nodes: Arc::new(AtomicU64::new(0)),
+            seldepth: 0,
+            root_depth: 0,
+            stopped: false,
+            pv_table: vec![Vec::new(); MAX_PLY + 1],
+            killers: vec![[None; 2]; MAX_PLY + 1],
+            history: History::new(),
+            null_moves: vec![false; MAX_PLY + 1],
+            lines: Vec::new(),
+        }
+    }
+
+    pub fn board(&self) -> &ChessBoard {
+        &self.board
+    }
+
+    pub fn set_signals(&mut self, signals: Arc<SearchSignals>) {
+        self.signals = signals;
+    }
+
+    /// Enables writing an `info` line after every completed iteration
+    pub fn set_output(&mut self, output: Output) {
+        self.output = Some(output);
+    }
+
+    /// Marks the search as a helper, which doesn't age the shared transposition table
+    /// since the main thread already did
+    pub fn set_helper(&mut self, helper: bool) {
+        self.helper = helper;
+    }
+
+    /// Counter the helpers of a multithreaded search add their nodes to,
+    /// so the main thread reports the nodes of every thread
+    pub fn set_helper_nodes(&mut self, helper_nodes: Arc<AtomicU64>) {
+        self.helper_nodes = helper_nodes;
+    }
+
+    /// Nodes searched so far, including those of the helper threads for the main thread
+    pub(crate) fn total_nodes(&self) -> u64 {
+        if self.helper {
+            self.nodes
+        } else {
+            self.nodes + self.helper_nodes.load(Ordering::Relaxed)
+        }
+    }
+
+    pub fn set_multi_pv(&mut self, multi_pv: usize) {
+        self.multi_pv = multi_pv.max(1);
+    }
+
+    pub fn set_selectivity(&mut self, selectivity: Selectivity) {
+        self.selectivity = selectivity;
+    }
+
+    /// Principal variation of the last completed iteration
+    pub fn pv(&self) -> &[ChessMove] {
+        self.lines.first().map_or(&[], |line| &line.2)
+    }
+
+    /// Best lines of the last completed iteration, ranked by score
+    pub fn lines(&self) -> &[PvLine] {
+        &self.lines
+    }
+
+    /// Updates the principal variation at `ply` with the move and the line following it
+    pub(crate) fn update_pv(&mut self, ply: usize, mv: ChessMove) {
+        let (head, tail) = self.pv_table.split_at_mut(ply + 1);
+        head[ply].clear();
+        head[ply].push(mv);
+        head[ply].extend_from_slice(&tail[0]);
+    }
+
+    /// Remembers a quiet move that caused a beta cutoff, so it gets searched early in sibling positions
+    pub(crate) fn store_killer(&mut self, ply: usize, mv: ChessMove) {
+        let killers = &mut self.killers[ply];
+        if killers[0] != Some(mv) {
+            killers[1] = killers[0];
+            killers[0] = Some(mv);
+        }
+    }
+
+    /// Searches with increasing depth until a limit is reached. <br>
+    /// Returns the best move of the last completed iteration, `None` if there is no legal move.
+    pub fn run(&mut self) -> Option<ChessMove> {
+        let mut best_mv = *self.board.all_possible_moves().first()?;
+        if !self.helper {
+            self.tt.new_search();
+        }
+        let max_depth = match (self.params.depth, self.params.mate) {
+            (Some(depth), _) => depth.clamp(1, MAX_DEPTH),
+            // a mate in N moves needs 2N - 1 plies
+            (None, Some(mate)) => (mate.saturating_mul(2).saturating_sub(1)).clamp(1, MAX_DEPTH),
+            (None, None) => MAX_DEPTH,
+        };
+
+        for depth in 1..=max_depth {
+            self.seldepth = 0;
+            let prev_lines = std::mem::take(&mut self.lines);
+            match self.aspiration_search(depth, &prev_lines) {
+                Some(lines) => {
+                    self.lines = lines;
+                    let (mv, score, _) = self.lines[0];
+                    best_mv = mv;
+                    for (i, line) in self.lines.iter().enumerate() {
+                        self.print_info(depth, i, line, Bound::Exact);
+                    }
+                    if let Some(mate) = self.params.mate
+                        && score >= MATE_SCORE - (2 * mate as i32 - 1)
+                    {
+                        break;
+                    }
+                }
+                None => {
+                    self.lines = prev_lines;
+                    break;
+                }
+            }
+            if let Some(budget) = self.budget
+                && self.time_exceeded(budget.soft)
+            {
+                break;
+            }
+        }
+        Some(best_mv)
+    }
+
+    /// Searches the iteration with a narrow window around the score of the previous iteration first,
+    /// widening it on the side the score fell out until the score is exact. <br>
+    /// Only a single line is searched with aspiration windows, the window of MultiPV depends on the worst line.
+    fn aspiration_search(&mut self, depth: u8, prev_lines: &[PvLine]) -> Option<Vec<PvLine>> {
+        let mut delta = ASPIRATION_DELTA;
+        let (mut alpha, mut beta) = match prev_lines.first() {
+            Some(&(_, score, _))
+                if depth >= ASPIRATION_MIN_DEPTH
+                    && self.multi_pv == 1
+                    && score.abs() < MATE_BOUND =>
+            {
+                (score - delta, score + delta)
+            }
+            _ => (-INFINITY, INFINITY),
+        };
+        loop {
+            let (lines, bound) = self.root_search(depth, prev_lines, alpha, beta)?;
+            delta *= 2;
+            match bound {
+                Bound::Exact => return Some(lines),
+                // fail high, the best move is better than expected
+                Bound::Lower => {
+                    self.print_info(depth, 0, &lines[0], bound);
+                    beta = (lines[0].1 + delta).min(INFINITY);
+                }
+                // fail low, every move is worse than expected
+                Bound::Upper => {
+                    if self.output.is_some()
+                        && let Some(prev) = prev_lines.first()
+                    {
+                        self.print_info(depth, 0, &(prev.0, alpha, prev.2.clone()), bound);
+                    }
+                    alpha = (alpha - delta).max(-INFINITY);
+                }
+            }
+        }
+    }
+
+    /// Writes the `info` line of the line with the given rank, if the search has an output
+    fn print_info(&self, depth: u8, rank: usize, line: &PvLine, bound: Bound) {
+        if let Some(output) = &self.output {
+            output.line(self.info(depth, rank, line, bound));
+        }
+    }
+
+    /// Statistics of the iteration for the line with the given rank
+    fn info(&self, depth: u8, rank: usize, line: &PvLine, bound: Bound) -> SearchInfo {
+        let (_, score, pv) = line;
+        SearchInfo {
+            depth,
+            seldepth: self.seldepth,
+            multipv: (self.multi_pv > 1).then_some(rank + 1),
+            score: *score,
+            bound,
+            nodes: self.total_nodes(),
+            time: self.start.elapsed(),
+            hashfull: self.tt.hashfull(),
+            pv: pv.clone(),
+        }
+    }
+
+    /// Stops the search if it got signaled or the node or the hard time limit is exceeded,
+    /// a helper adds the last 1024 nodes to the shared counter
+    pub(crate) fn check_limits(&mut self) {
+        if self.helper {
+            self.helper_nodes.fetch_add(1024, Ordering::Relaxed);
+        }
+        if self.signals.stop.load(Ordering::Relaxed) {
+            self.stopped = true;
+        }
+        if let Some(nodes) = self.params.nodes
+            && self.total_nodes() >= nodes
+        {
+            self.stopped = true;
+        }
+        if let Some(budget) = self.budget
+            && self.time_exceeded(budget.hard)
+        {
+            self.stopped = true;
+        }
+    }
+
+    /// Time limits don't apply while pondering, the clock starts when the GUI sends `ponderhit`
+    fn time_exceeded(&mut self, limit: Duration) -> bool {
+        if self.pondering {
+            if self.signals.ponder.load(Ordering::Relaxed) {
+                return false;
+            }
+            self.pondering = false;
+            self.start = Instant::now();
+        }
+        self.start.elapsed() >= limit
+    }
+}
@@ -0,0 +1,172 @@
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::thread::{self, JoinHandle};
+use std::time::Duration;
+
+use crate::core::limits::GoParams;
+use crate::core::output::Output;
+use crate::core::search::{Search, SearchSignals};
+use crate::core::selectivity::Selectivity;
+use crate::core::tt::{DEFAULT_HASH_MB, TranspositionTable};
+use crate::models::board::ChessBoard;
+
+pub const MAX_MULTI_PV: usize = 256;
+pub const MAX_THREADS: usize = 64;
+
+/// Runs the search on a worker thread, so the UCI loop can still answer `stop`, `ponderhit` and `isready`. <br>
+/// With more than one thread (Lazy SMP) helper threads search the same position and share their results
+/// through the transposition table, only the result of the main thread is reported. <br>
+/// The transposition table is kept between searches.
+pub struct SearchThread {
+    handle: Option<JoinHandle<()>>,
+    signals: Arc<SearchSignals>,
+    tt: Arc<TranspositionTable>,
+    /// Destination of the `info` and `bestmove` lines
+    output: Output,
+    /// Set while an infinite search is running, it only ends with `stop`
+    infinite: bool,
+    multi_pv: usize,
+    selectivity: Selectivity,
+    /// Number of threads searching, the UCI `Threads` option
+    threads: usize,
+}
+
+impl SearchThread {
+    pub fn new(output: Output) -> Self {
+        Self {
+            handle: None,
+            signals: Arc::new(SearchSignals::default()),
+            tt: Arc::new(TranspositionTable::new(DEFAULT_HASH_MB)),
+            output,
+            infinite: false,
+            multi_pv: 1,
+            selectivity: Selectivity::default(),
+            threads: 1,
+        }
+    }
+
+    /// Starts searching the position, the worker prints `bestmove` when it is done.
+    /// A search that is still running gets stopped first.
+    pub fn start(&mut self, board: ChessBoard, params: GoParams) {
+        self.stop();
+        self.signals = Arc::new(SearchSignals::default());
+        self.signals.ponder.store(params.ponder, Ordering::Relaxed);
+        self.infinite = params.infinite;
+
+        let signals = Arc::clone(&self.signals);
+        let mut search = Search::with_tt(board, params, Arc::clone(&self.tt));
+        search.set_signals(Arc::clone(&signals));
+        search.set_output(self.output.clone());
+        search.set_multi_pv(self.multi_pv);
+        search.set_selectivity(self.selectivity);
+
+        // the helpers run without limits until the main thread is done
+        let helper_signals = Arc::new(SearchSignals::default());
+        let helper_nodes = Arc::new(AtomicU64::new(0));
+        search.set_helper_nodes(Arc::clone(&helper_nodes));
+        let helper_params = GoParams {
+            depth: params.depth,
+            mate: params.mate,
+            ..GoParams::default()
+        };
+        let helpers: Vec<Search> = (1..self.threads)
+            .map(|_| {
+                let mut helper = Search::with_tt(board, helper_params, Arc::clone(&self.tt));
+                helper.set_helper(true);
+                helper.set_signals(Arc::clone(&helper_signals));
+                helper.set_helper_nodes(Arc::clone(&helper_nodes));
+                helper.set_multi_pv(self.multi_pv);
+                helper.set_selectivity(self.selectivity);
+                helper
+            })
+            .collect();
+
+        let output = self.output.clone();
+        self.handle = Some(thread::spawn(move || {
+            let helpers: Vec<JoinHandle<()>> = helpers
+                .into_iter()
+                .map(|mut helper| {
+                    thread::spawn(move || {
+                        helper.run();
+                    })
+                })
+                .collect();
+            let best_mv = search.run();
+            helper_signals.stop.store(true, Ordering::Relaxed);
+            for helper in helpers {
+                helper.join().expect("Helper search thread panicked");
+            }
+            // the GUI expects no bestmove before `stop` when searching infinite, or before `ponderhit` when pondering
+            while !signals.stop.load(Ordering::Relaxed)
+                && (params.infinite || signals.ponder.load(Ordering::Relaxed))
+            {
+                thread::sleep(Duration::from_millis(1));
+            }
+            match (best_mv, search.pv().get(1)) {
+                (Some(best_mv), Some(ponder_mv)) => output.line(format!(
+                    "bestmove {} ponder {}",
+                    best_mv.to_str(),
+                    ponder_mv.to_str()
+                )),
+                (Some(best_mv), None) => output.line(format!("bestmove {}", best_mv.to_str())),
+                (None, _) => output.line("bestmove 0000"),
+            }
+        }));
+    }
+
+    /// Stops the running search and waits until it printed its `bestmove`
+    pub fn stop(&mut self) {
+        self.signals.stop.store(true, Ordering::Relaxed);
+        self.wait();
+    }
+
+    /// Waits until the running search finished on its own
+    pub fn wait(&mut self) {
+        if let Some(handle) = self.handle.take() {
+            handle.join().expect("Search thread panicked");
+        }
+    }
+
+    /// Lets the running search finish within its limits, a ponder search continues as a normal search
+    /// and an infinite search gets stopped, since no `stop` will follow
+    pub fn finish(&mut self) {
+        if self.infinite {
+            self.stop();
+        } else {
+            self.ponderhit();
+            self.wait();
+        }
+    }
+
+    /// The opponent played the expected move, so the ponder search continues as a normal search
+    pub fn ponderhit(&self) {
+        self.signals.ponder.store(false, Ordering::Relaxed);
+    }
+
+    /// Resizes the transposition table to the value of the UCI `Hash` option in MB
+    pub fn resize_tt(&mut self, size_mb: usize) {
+        self.stop();
+        self.tt = Arc::new(TranspositionTable::new(size_mb));
+    }
+
+    /// Number of best lines reported by the following searches, the UCI `MultiPV` option
+    pub fn set_multi_pv(&mut self, multi_pv: usize) {
+        self.multi_pv = multi_pv.clamp(1, MAX_MULTI_PV);
+    }
+
+    /// Enables or disables a pruning technique of the following searches by its UCI option,
+    /// returns `false` if the option doesn't exist
+    pub fn set_selectivity(&mut self, name: &str, enabled: bool) -> bool {
+        self.selectivity.set(name, enabled)
+    }
+
+    /// Number of threads used by the following searches, the UCI `Threads` option
+    pub fn set_threads(&mut self, threads: usize) {
+        self.threads = threads.clamp(1, MAX_THREADS);
+    }
+
+    pub fn clear_tt(&mut self) {
+        self.stop();
+        self.tt.clear();
+    }
+}
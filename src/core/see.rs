@@ -0,0 +1,118 @@
+use crate::models::{
+    board::{Bitboard, ChessBoard},
+    chessmove::ChessMove,
+    piece::{Color, Piece, Role},
+};
+
+/// Roles in the order they join an exchange, the least valuable attacker captures first
+const EXCHANGE_ORDER: [Role; 6] = [
+    Role::Pawn,
+    Role::Knight,
+    Role::Bishop,
+    Role::Rook,
+    Role::Queen,
+    Role::King,
+];
+
+impl ChessBoard {
+    /// Static exchange evaluation of a capture. <br>
+    /// Returns the material the side to move wins (or loses, if negative) when both sides keep recapturing
+    /// on the destination square with their least valuable attacker and stop as soon as it doesn't pay off.
+    /// Sliders behind other attackers join the exchange once the square in front of them got cleared. <br>
+    /// A move to an empty square evaluates whether the moving piece can be won there.
+    pub fn see(&self, mv: ChessMove) -> i32 {
+        let curr_sq = mv.get_curr_square_as_index();
+        let dest_sq = mv.get_dest_square_as_index();
+        let Some(attacker) = self.piece_on(curr_sq) else {
+            return 0;
+        };
+        let mut board = *self;
+        let mut gain = [0; 32];
+
+        // the initial capture
+        let is_pawn = attacker.role() == Role::Pawn;
+        match self.piece_on(dest_sq) {
+            Some(victim) => {
+                gain[0] = victim.value();
+                board.remove_piece(victim.into(), dest_sq);
+            }
+            None if is_pawn && dest_sq == self.get_en_passant() => {
+                gain[0] = Role::Pawn.value();
+                let captured_sq = ChessBoard::en_passant_pawn_square(dest_sq, attacker.color());
+                board.remove_piece(
+                    Piece::new(Role::Pawn, !attacker.color()).into(),
+                    captured_sq,
+                );
+            }
+            None => {}
+        }
+        let mut on_square = match mv.promotion() {
+            Some(promoted) => {
+                gain[0] += promoted.value() - Role::Pawn.value();
+                promoted.value()
+            }
+            None => attacker.value(),
+        };
+        board.remove_piece(attacker.into(), curr_sq);
+
+        let mut color = !attacker.color();
+        let mut depth = 0;
+        while let Some(piece) = board.least_valuable_attacker(dest_sq, color) {
+            // the king can't recapture into a defended square
+            if piece.role() == Role::King
+                && board.least_valuable_attacker(dest_sq, !color).is_some()
+            {
+                break;
+            }
+            // neither side can improve by continuing the exchange, so the capture doesn't happen
+            if (-gain[depth]).max(on_square - gain[depth]) < 0 || depth == gain.len() - 1 {
+                break;
+            }
+            depth += 1;
+            gain[depth] = on_square - gain[depth - 1];
+            let sq = (board.get_bitboard(piece.into()) & board.attackers_of(dest_sq, color))
+                .into_iter()
+                .next()
+                .unwrap();
+            board.remove_piece(piece.into(), sq);
+            on_square = piece.value();
+            color = !color;
+        }
+        // each side only continues the exchange if it gains from it
+        while depth > 0 {
+            gain[depth - 1] = -(-gain[depth - 1]).max(gain[depth]);
+            depth -= 1;
+        }
+        gain[0]
+    }
+
+    /// Checks if the static exchange evaluation of the move is at least `threshold`
+    pub fn see_ge(&self, mv: ChessMove, threshold: i32) -> bool {
+        self.see(mv) >= threshold
+    }
+
+    /// Calculates a mask of every piece of a color attacking the square, sliders are blocked by any piece
+    pub fn attackers_of(&self, sq_index: u16, color: Color) -> Bitboard {
+        let pieces = |role: Role| self.get_bitboard(Piece::new(role, color).into());
+
+        // a pawn attacks the square if a pawn of the other color on the square would attack it
+        let pawn_attackers = ChessBoard::calc_pawn_attackmask(sq_index, !color);
+        let diagonal = pieces(Role::Bishop) | pieces(Role::Queen);
+        let straight = pieces(Role::Rook) | pieces(Role::Queen);
+
+        (pawn_attackers & pieces(Role::Pawn))
+            | (ChessBoard::calc_knight_attackmask(sq_index) & pieces(Role::Knight))
+            | (self.calc_bishop_attackmask(sq_index) & diagonal)
+            | (self.calc_rook_attackmask(sq_index) & straight)
+            | (ChessBoard::calc_king_attackmask(sq_index) & pieces(Role::King))
+    }
+
+    /// Finds the least valuable piece of a color attacking the square
+    fn least_valuable_attacker(&self, sq_index: u16, color: Color) -> Option<Piece> {
+        let attackers = self.attackers_of(sq_index, color);
+        EXCHANGE_ORDER
+            .into_iter()
+            .map(|role| Piece::new(role, color))
+            .find(|&piece| (self.get_bitboard(piece.into()) & attackers).to_u64() != 0)
+    }
+}
@@ -0,0 +1,80 @@
+/// Margin per ply of the reverse futility pruning, a static evaluation this far above beta fails high
+pub const REVERSE_FUTILITY_MARGIN: i32 = 120;
+/// Margin of the futility pruning by remaining depth, quiet moves can't raise alpha by more
+pub const FUTILITY_MARGINS: [i32; 3] = [0, 200, 400];
+/// Number of moves searched at full depth before late move reductions apply
+pub const LMR_FULL_DEPTH_MOVES: usize = 3;
+
+/// Techniques the alpha-beta search uses to skip or shorten the search of unpromising moves,
+/// and to search forcing moves deeper. <br>
+/// Each one is a UCI option, so they can be tested against each other.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Selectivity {
+    /// Null-move pruning, skipped in check and when the side to move has only pawns left
+    pub null_move: bool,
+    /// Late move reductions of quiet moves ordered late
+    pub lmr: bool,
+    /// Reverse futility pruning, a node fails high if the static evaluation is far above beta
+    pub reverse_futility: bool,
+    /// Futility pruning, quiet moves are skipped near the horizon if the static evaluation is far below alpha
+    pub futility: bool,
+    /// Moves giving check are searched one ply deeper, up to twice the depth of the iteration
+    pub check_extensions: bool,
+    /// Delta pruning and pruning of captures losing material in the quiescence search
+    pub qsearch_pruning: bool,
+}
+
+impl Selectivity {
+    /// Names of the UCI options, in the order they are advertised
+    pub const OPTIONS: [&str; 6] = [
+        "NullMove",
+        "LMR",
+        "ReverseFutility",
+        "Futility",
+        "CheckExtensions",
+        "QSearchPruning",
+    ];
+
+    /// Every technique disabled, the search visits the same moves as a plain alpha-beta search
+    /// with a quiescence search over every capture
+    // allowed dead code, only the tests compare against the plain search
+    #[allow(dead_code)]
+    pub fn none() -> Self {
+        Self {
+            null_move: false,
+            lmr: false,
+            reverse_futility: false,
+            futility: false,
+            check_extensions: false,
+            qsearch_pruning: false,
+        }
+    }
+
+    /// Sets the technique of a UCI option, returns `false` if the option doesn't exist
+    pub fn set(&mut self, name: &str, enabled: bool) -> bool {
+        let option = match name {
+            "NullMove" => &mut self.null_move,
+            "LMR" => &mut self.lmr,
+            "ReverseFutility" => &mut self.reverse_futility,
+            "Futility" => &mut self.futility,
+            "CheckExtensions" => &mut self.check_extensions,
+            "QSearchPruning" => &mut self.qsearch_pruning,
+            _ => return false,
+        };
+        *option = enabled;
+        true
+    }
+}
+
+impl Default for Selectivity {
+    fn default() -> Self {
+        Self {
+            null_move: true,
+            lmr: true,
+            reverse_futility: true,
+            futility: true,
+            check_extensions: true,
+            qsearch_pruning: true,
+        }
+    }
+}
@@ -0,0 +1,211 @@
+use std::mem::size_of;
+use std::sync::atomic::{AtomicU8, AtomicU64, Ordering};
+
+use crate::core::info::MATE_BOUND;
+use crate::models::chessmove::ChessMove;
+
+/// Default size of the table in MB, as advertised with the UCI `Hash` option
+pub const DEFAULT_HASH_MB: usize = 1;
+pub const MAX_HASH_MB: usize = 128;
+
+/// Describes how the stored score relates to the real score of the position
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Bound {
+    Exact,
+    /// The real score is at least the stored score (fail high)
+    Lower,
+    /// The real score is at most the stored score (fail low)
+    Upper,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct TtEntry {
+    pub key: u64,
+    pub best_mv: Option<ChessMove>,
+    pub score: i32,
+    pub depth: u8,
+    pub bound: Bound,
+    age: u8,
+}
+
+impl TtEntry {
+    /// Packs everything but the key into 64 bits: move (16), score (32), depth (8), bound (2) and age (6). <br>
+    /// The bound is never 0, so an empty slot can't be mistaken for an entry.
+    fn to_data(self) -> u64 {
+        let bound = match self.bound {
+            Bound::Exact => 1,
+            Bound::Lower => 2,
+            Bound::Upper => 3,
+        };
+        self.best_mv.map_or(0, |mv| mv.to_u16() as u64)
+            | (self.score as u32 as u64) << 16
+            | (self.depth as u64) << 48
+            | bound << 56
+            | ((self.age & AGE_MASK) as u64) << 58
+    }
+
+    fn from_data(key: u64, data: u64) -> Option<Self> {
+        let bound = match (data >> 56) & 0b11 {
+            1 => Bound::Exact,
+            2 => Bound::Lower,
+            3 => Bound::Upper,
+            _ => return None,
+        };
+        // a move from a8 to a8 doesn't exist, so it marks a missing move
+        let best_mv = Some(ChessMove::new(data as u16)).filter(|mv| mv.to_u16() != 0);
+        Some(Self {
+            key,
+            best_mv,
+            score: (data >> 16) as u32 as i32,
+            depth: (data >> 48) as u8,
+            bound,
+            age: (data >> 58) as u8,
+        })
+    }
+}
+
+/// Only the lowest bits of the age are stored, it wraps around after 64 searches
+const AGE_MASK: u8 = 0b11_1111;
+
+/// Slot of the table, written and read by several search threads without a lock. <br>
+/// The key is stored xored with the data, so an entry torn by two threads writing at once
+/// doesn't match its key any more and gets ignored.
+#[derive(Default)]
+struct Slot {
+    key_xor_data: AtomicU64,
+    data: AtomicU64,
+}
+
+impl Slot {
+    fn load(&self) -> Option<TtEntry> {
+        let data = self.data.load(Ordering::Relaxed);
+        let key = self.key_xor_data.load(Ordering::Relaxed) ^ data;
+        TtEntry::from_data(key, data)
+    }
+
+    fn save(&self, entry: TtEntry) {
+        let data = entry.to_data();
+        self.key_xor_data.store(entry.key ^ data, Ordering::Relaxed);
+        self.data.store(data, Ordering::Relaxed);
+    }
+}
+
+/// Fixed size hash table of searched positions, indexed by their Zobrist key. <br>
+/// Entries of an older search or with a lower depth get replaced first.
+/// The table is shared by the threads of a search without locking, see `Slot`.
+pub struct TranspositionTable {
+    slots: Vec<Slot>,
+    age: AtomicU8,
+}
+
+impl TranspositionTable {
+    /// Allocates an empty table with the given size in MB
+    pub fn new(size_mb: usize) -> Self {
+        let size_mb = size_mb.clamp(1, MAX_HASH_MB);
+        let len = size_mb * 1024 * 1024 / size_of::<Slot>();
+        Self {
+            slots: (0..len).map(|_| Slot::default()).collect(),
+            age: AtomicU8::new(0),
+        }
+    }
+
+    pub fn clear(&self) {
+        for slot in &self.slots {
+            slot.key_xor_data.store(0, Ordering::Relaxed);
+            slot.data.store(0, Ordering::Relaxed);
+        }
+        self.age.store(0, Ordering::Relaxed);
+    }
+
+    /// Marks the entries of previous searches as old, so they get replaced first
+    pub fn new_search(&self) {
+        self.age.fetch_add(1, Ordering::Relaxed);
+    }
+
+    fn age(&self) -> u8 {
+        self.age.load(Ordering::Relaxed) & AGE_MASK
+    }
+
+    /// Permill of the entries used by the current search, sampled from the first 1000 entries
+    pub fn hashfull(&self) -> u16 {
+        let sample = self.slots.len().min(1000);
+        let age = self.age();
+        let used = self.slots[..sample]
+            .iter()
+            .filter(|slot| slot.load().is_some_and(|entry| entry.age == age))
+            .count();
+        (used * 1000 / sample) as u16
+    }
+
+    fn index(&self, key: u64) -> usize {
+        // mapping the key evenly onto the table without a modulo
+        ((key as u128 * self.slots.len() as u128) >> 64) as usize
+    }
+
+    /// Looks up the position, the returned score is already adjusted to the distance to the root
+    pub fn probe(&self, key: u64, ply: u16) -> Option<TtEntry> {
+        let entry = self.slots[self.index(key)].load()?;
+        if entry.key != key {
+            return None;
+        }
+        Some(TtEntry {
+            score: score_from_tt(entry.score, ply),
+            ..entry
+        })
+    }
+
+    pub fn store(
+        &self,
+        key: u64,
+        best_mv: Option<ChessMove>,
+        score: i32,
+        depth: u8,
+        bound: Bound,
+        ply: u16,
+    ) {
+        let slot = &self.slots[self.index(key)];
+        let age = self.age();
+        let old = slot.load();
+        if let Some(old) = old
+            && old.key != key
+            && old.age == age
+            && old.depth > depth
+        {
+            return;
+        }
+        // keeping the best move of a previous search of this position, if the new search didn't find one
+        let best_mv = match old {
+            Some(old) if old.key == key && best_mv.is_none() => old.best_mv,
+            _ => best_mv,
+        };
+        slot.save(TtEntry {
+            key,
+            best_mv,
+            score: score_to_tt(score, ply),
+            depth,
+            bound,
+            age,
+        });
+    }
+}
+
+/// Mate scores are stored as the distance to mate from the stored position instead of the root
+fn score_to_tt(score: i32, ply: u16) -> i32 {
+    if score > MATE_BOUND {
+        score + ply as i32
+    } else if score < -MATE_BOUND {
+        score - ply as i32
+    } else {
+        score
+    }
+}
+
+fn score_from_tt(score: i32, ply: u16) -> i32 {
+    if score > MATE_BOUND {
+        score - ply as i32
+    } else if score < -MATE_BOUND {
+        score + ply as i32
+    } else {
+        score
+    }
+}
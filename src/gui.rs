@@ -1,17 +1,21 @@
-use crate::models::{board::ChessBoard, chessmove::ChessMove};
+use crate::core::info::uci_score;
+use crate::core::output::Output;
+use crate::models::board::ChessBoard;
 use rand::seq::IndexedRandom;
-use std::io;
+use std::io::BufRead;
 
 const DEPTH: i32 = 5;
+// number of best lines shown by the lines command
+const LINES: usize = 3;
 
 pub fn parse_fen_pieces_to_board(fen: &str) -> Vec<Vec<char>> {
     fen.split('/')
         .map(|rank| {
             let mut row = Vec::new();
             for ch in rank.chars() {
-                if ch.is_digit(10) {
+                if ch.is_ascii_digit() {
                     let empty_squares = ch.to_digit(10).unwrap();
-                    row.extend(std::iter::repeat('·').take(empty_squares as usize));
+                    row.extend(std::iter::repeat_n('·', empty_squares as usize));
                 } else {
                     row.push(ch);
                 }
@@ -21,46 +25,48 @@ pub fn parse_fen_pieces_to_board(fen: &str) -> Vec<Vec<char>> {
         .collect()
 }
 
-pub fn testing() {
+pub fn testing(input: &mut impl BufRead, output: &Output) {
     let mut chess_board = ChessBoard::starting_position();
-    let mut depth = 0;
+    let mut depth: u8 = 0;
 
     loop {
-        let fen_string = String::from(chess_board.to_fen());
+        let fen_string = chess_board.to_fen();
         let fen_pieces = match fen_string.find(' ') {
             Some(index) => fen_string[0..index].to_string(),
             None => fen_string[..].to_string(),
         };
-        let board = parse_fen_pieces_to_board(&fen_pieces.as_str());
-        println!("\n\n  +------------------------+");
+        let board = parse_fen_pieces_to_board(fen_pieces.as_str());
+        output.line("\n\n  +------------------------+");
         for (i, row) in board.iter().enumerate() {
-            print!("{} |", 8 - i);
+            let mut line = format!("{} |", 8 - i);
             for piece in row {
-                print!(" {} ", piece);
+                line.push_str(&format!(" {} ", piece));
             }
-            println!("|");
+            line.push('|');
+            output.line(line);
         }
-        println!("  +------------------------+");
-        println!("    a  b  c  d  e  f  g  h");
+        output.line("  +------------------------+");
+        output.line("    a  b  c  d  e  f  g  h");
 
-        let mut input = String::from("");
+        let mut line = String::from("");
         let all_moves = chess_board.all_possible_moves();
 
-        match io::stdin().read_line(&mut input) {
-            Ok(_) => match input.trim() {
+        match input.read_line(&mut line) {
+            Ok(0) => break,
+            Ok(_) => match line.trim() {
                 "quit" => {
-                    println!("Exiting");
+                    output.line("Exiting");
                     break;
                 }
                 "rand" => {
-                    println!("Picking random move");
+                    output.line("Picking random move");
                     let mut rng = rand::rng();
                     let rand_mv = all_moves.choose(&mut rng).cloned().unwrap();
-                    println!("Chosen Move: {:?}\n", rand_mv);
+                    output.line(format!("Chosen Move: {:?}\n", rand_mv));
                     chess_board.make_move(rand_mv);
                 }
                 "all" => {
-                    println!("Calculating all positions");
+                    output.line("Calculating all positions");
                     let mut possible_boards: Vec<ChessBoard> = vec![chess_board];
                     let mut debug_check_counter = 0;
                     for i in 1..=DEPTH {
@@ -79,41 +85,61 @@ pub fn testing() {
                             }
                         }
                         possible_boards = new_boards;
-                        println!(
+                        output.line(format!(
                             "Depth: {}\nCount of possible positions: {}",
                             i,
                             possible_boards.len()
-                        );
-                        println!("Positions with check: {}", debug_check_counter)
+                        ));
+                        output.line(format!("Positions with check: {}", debug_check_counter))
                     }
                 }
                 "best" => {
-                    let best_mv = chess_board.best_mv(depth);
-                    println!("best move: {}", best_mv.to_str());
+                    // the depth counts the plies after the root move
+                    let best_mv = chess_board.best_mv(depth.saturating_add(1));
+                    output.line(format!("best move: {}", best_mv.to_str()));
+                }
+                "lines" => {
+                    for (i, (_, score, pv)) in chess_board
+                        .multi_pv(depth.saturating_add(1), LINES)
+                        .iter()
+                        .enumerate()
+                    {
+                        let pv_str: Vec<String> = pv.iter().map(|mv| mv.to_str()).collect();
+                        output.line(format!(
+                            "{}. {} ({})",
+                            i + 1,
+                            pv_str.join(" "),
+                            uci_score(*score)
+                        ));
+                    }
                 }
                 "fen" => {
-                    println!("input fen");
+                    output.line("input fen");
                     let mut fen_string = String::new();
-                    io::stdin()
+                    input
                         .read_line(&mut fen_string)
                         .expect("Failed to read line");
-                    chess_board = ChessBoard::from_fen(&fen_string)
+                    match ChessBoard::try_from_fen_strict(&fen_string) {
+                        Ok(board) => chess_board = board,
+                        Err(err) => output.line(format!("invalid fen: {}", err)),
+                    }
                 }
                 "depth" => {
-                    println!("input depth");
+                    output.line("input depth");
                     let mut depth_string = String::new();
-                    io::stdin()
+                    input
                         .read_line(&mut depth_string)
                         .expect("Failed to read line");
                     depth = depth_string.trim().parse::<u8>().unwrap_or(0);
                 }
                 _ => {
-                    let mv = ChessMove::from_str(&input);
-                    chess_board.make_move(mv);
+                    if let Err(err) = chess_board.try_make_move(&line) {
+                        output.line(err);
+                    }
                 }
             },
             Err(error) => {
-                println!("Error: {}\nExiting now", error);
+                output.line(format!("Error: {}\nExiting now", error));
                 break;
             }
         }
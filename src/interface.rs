@@ -19,3 +19,11 @@ pub fn abs_diff_u16(a: u16, b: u16) -> u16 {
     let mask = ((a < b) as u16).wrapping_neg(); // 0 wenn a >= b, sonst 0xFFFFFFFF
     (diff ^ mask).wrapping_sub(mask)
 }
+
+/// Name of a square index (0 = a8, 63 = h1) like `e4`
+pub fn square_name(sq: u16) -> String {
+    let mut name = String::new();
+    name.push(i8_to_file((sq & 0b111) as i8 + 1));
+    name.push(i8_to_rank(8 - (sq >> 3) as i8));
+    name
+}
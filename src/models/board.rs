@@ -1,14 +1,16 @@
 use core::fmt;
 
-use crate::interface::abs_diff_u16;
+use crate::interface::{abs_diff_u16, square_name};
 
 use super::{
-    chessmove::ChessMove,
+    chessmove::{ChessMove, MoveParseError},
+    fen::FenError,
     piece::{
-        Piece,
+        Color, Piece, Role,
         castling::{self},
     },
     piecesquaretables::PIECE_SQUARE_TABLES,
+    zobrist::ZOBRIST,
 };
 
 #[derive(Clone, Copy, Debug, PartialEq)]
@@ -19,6 +21,24 @@ pub struct ChessBoard {
     // en_passant represents the index at which a pan should be captured
     // e.g.for e2e4, en_passant would be 44 (e3)
     en_passant: u16,
+    // number of halfmoves since the last capture or pawn move, for the fifty-move rule
+    halfmove_clock: u16,
+    // number of the current move, starting at 1 and incremented after every black move
+    fullmove_number: u16,
+    // Zobrist key of the position, kept up to date with every change of the board
+    hash: u64,
+}
+
+/// State of the board before a move that can't be recovered from the move itself,
+/// returned by `make_move` to take the move back with `unmake_move`
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Undo {
+    captured: Option<Piece>,
+    castling_rights: u8,
+    en_passant: u16,
+    halfmove_clock: u16,
+    fullmove_number: u16,
+    hash: u64,
 }
 // allowed dead code, since only some smaller setter functions aren't used
 #[allow(dead_code)]
@@ -29,6 +49,9 @@ impl ChessBoard {
             white_to_move: true,
             castling_rights: castling::ALL,
             en_passant: 64, // 64 = no en passant available
+            halfmove_clock: 0,
+            fullmove_number: 1,
+            hash: ZOBRIST.castling[castling::ALL as usize],
         }
     }
 
@@ -54,110 +77,119 @@ impl ChessBoard {
         board.white_to_move = true;
         board.castling_rights = castling::ALL;
         board.en_passant = 64;
+        board.hash = board.calc_hash();
 
         board
     }
 
+    /// Reads a position in Forsyth-Edwards Notation, panics if it is malformed. <br>
+    /// Input that doesn't come from the engine itself should go through `try_from_fen`.
     pub fn from_fen(fen: &str) -> Self {
+        Self::try_from_fen(fen).unwrap_or_else(|err| panic!("Invalid FEN '{}': {}", fen, err))
+    }
+
+    /// Reads a position in Forsyth-Edwards Notation,
+    /// the halfmove clock and fullmove number may be left out and default to 0 and 1
+    pub fn try_from_fen(fen: &str) -> Result<Self, FenError> {
         let parts: Vec<&str> = fen.split_whitespace().collect();
+        if parts.len() != 4 && parts.len() != 6 {
+            return Err(FenError::WrongFieldCount(parts.len()));
+        }
 
         let mut board = Self::new();
-        // Clear all bitboards before setting
-        for i in 0..12 {
-            board.bitboards[i] = Bitboard::new(0);
-        }
 
         // Piece placement
         let ranks: Vec<&str> = parts[0].split('/').collect();
-
+        if ranks.len() != 8 {
+            return Err(FenError::WrongRankCount(ranks.len()));
+        }
         for (rank_idx, rank_str) in ranks.iter().enumerate() {
             let mut file_idx = 0;
             for char_code in rank_str.chars() {
-                if char_code.is_digit(10) {
-                    let empty_squares = char_code.to_digit(10).unwrap() as usize;
-                    file_idx += empty_squares;
+                if let Some(empty_squares) = char_code.to_digit(10)
+                    && (1..=8).contains(&empty_squares)
+                {
+                    file_idx += empty_squares as usize;
                 } else {
-                    let piece_type = match char_code {
-                        'P' => Some(Piece::WhitePawn),
-                        'N' => Some(Piece::WhiteKnight),
-                        'B' => Some(Piece::WhiteBishop),
-                        'R' => Some(Piece::WhiteRook),
-                        'Q' => Some(Piece::WhiteQueen),
-                        'K' => Some(Piece::WhiteKing),
-                        'p' => Some(Piece::BlackPawn),
-                        'n' => Some(Piece::BlackKnight),
-                        'b' => Some(Piece::BlackBishop),
-                        'r' => Some(Piece::BlackRook),
-                        'q' => Some(Piece::BlackQueen),
-                        'k' => Some(Piece::BlackKing),
-                        _ => {
-                            continue;
-                        }
-                    };
-
-                    if let Some(piece) = piece_type {
-                        let square_index = (rank_idx * 8) + file_idx;
-                        board.bitboards[piece as usize].set_bit(square_index as u16);
+                    let piece =
+                        Piece::from_char(char_code).ok_or(FenError::UnknownPiece(char_code))?;
+                    // squares past the h-file would end up on the next rank
+                    if file_idx < 8 {
+                        board.bitboards[piece as usize].set_bit((rank_idx * 8 + file_idx) as u16);
                     }
                     file_idx += 1;
                 }
             }
+            if file_idx != 8 {
+                return Err(FenError::BadRankLength {
+                    rank: 8 - rank_idx,
+                    length: file_idx,
+                });
+            }
         }
 
         // Active color
         board.white_to_move = match parts[1] {
             "w" => true,
             "b" => false,
-            _ => true,
+            side => return Err(FenError::BadSideToMove(side.to_string())),
         };
 
-        // Castling availability
+        // Castling availability, each right at most once
         board.castling_rights = 0;
-        for c in parts[2].chars() {
-            match c {
-                'K' => board.castling_rights |= castling::WHITE_K,
-                'Q' => board.castling_rights |= castling::WHITE_Q,
-                'k' => board.castling_rights |= castling::BLACK_K,
-                'q' => board.castling_rights |= castling::BLACK_Q,
-                '-' => {} // No castling rights
-                _ => {}
+        if parts[2] != "-" {
+            for c in parts[2].chars() {
+                let right = match c {
+                    'K' => castling::WHITE_K,
+                    'Q' => castling::WHITE_Q,
+                    'k' => castling::BLACK_K,
+                    'q' => castling::BLACK_Q,
+                    _ => return Err(FenError::BadCastling(parts[2].to_string())),
+                };
+                if board.castling_rights & right != 0 {
+                    return Err(FenError::BadCastling(parts[2].to_string()));
+                }
+                board.castling_rights |= right;
             }
         }
 
-        // En passant target square
+        // En passant target square, behind a pawn of the side that just moved
         board.en_passant = 64;
         if parts[3] != "-" {
-            let file_char = parts[3].chars().next().unwrap();
-            let rank_char = parts[3].chars().nth(1).unwrap();
-
-            let file = match file_char {
-                'a' => 0,
-                'b' => 1,
-                'c' => 2,
-                'd' => 3,
-                'e' => 4,
-                'f' => 5,
-                'g' => 6,
-                'h' => 7,
-                _ => 0,
-            };
-
-            let rank = match rank_char {
-                '1' => 7,
-                '2' => 6,
-                '3' => 5,
-                '4' => 4,
-                '5' => 3,
-                '6' => 2,
-                '7' => 1,
-                '8' => 0,
-                _ => 0,
+            let bad_square = || FenError::BadEnPassant(parts[3].to_string());
+            let expected_rank = if board.white_to_move { b'6' } else { b'3' };
+            let (file, rank) = match parts[3].as_bytes() {
+                &[file, rank] => (file, rank),
+                _ => return Err(bad_square()),
             };
+            if !(b'a'..=b'h').contains(&file) || rank != expected_rank {
+                return Err(bad_square());
+            }
+            // Convert file/rank to the bitboard index (0=a8, 63=h1)
+            board.en_passant = (b'8' - rank) as u16 * 8 + (file - b'a') as u16;
+        }
 
-            // Convert file/rank to your bitboard index (0=a8, 63=h1)
-            board.en_passant = (rank * 8 + file) as u16;
+        // Halfmove clock and fullmove number, FENs without them start a game
+        if let (Some(halfmove), Some(fullmove)) = (parts.get(4), parts.get(5)) {
+            board.halfmove_clock = halfmove
+                .parse()
+                .map_err(|_| FenError::BadHalfmoveClock(halfmove.to_string()))?;
+            board.fullmove_number = fullmove
+                .parse()
+                .ok()
+                .filter(|&number| number >= 1)
+                .ok_or_else(|| FenError::BadFullmoveNumber(fullmove.to_string()))?;
         }
-        board
+        board.hash = board.calc_hash();
+        Ok(board)
+    }
+
+    /// Reads a position in Forsyth-Edwards Notation like `try_from_fen`,
+    /// but also rejects positions that can't come up in a game, see `validate`
+    pub fn try_from_fen_strict(fen: &str) -> Result<Self, FenError> {
+        let board = Self::try_from_fen(fen)?;
+        board.validate().map_err(FenError::InvalidPosition)?;
+        Ok(board)
     }
 
     pub fn get_bitboards(&self) -> [Bitboard; 12] {
@@ -174,11 +206,13 @@ impl ChessBoard {
     pub fn set_bitboard(&mut self, index: usize, bb: Bitboard) {
         if index < 12 {
             self.bitboards[index] = bb;
+            self.hash = self.calc_hash();
         }
     }
 
     pub fn set_bitboards(&mut self, bitboards: [Bitboard; 12]) {
         self.bitboards = bitboards;
+        self.hash = self.calc_hash();
     }
 
     pub fn get_all_pieces(&self) -> Bitboard {
@@ -186,25 +220,47 @@ impl ChessBoard {
         Bitboard(all)
     }
 
-    pub fn get_w_pieces(&self) -> Bitboard {
-        let w_pieces_u64 = self.get_bitboards()[0..6]
+    /// Returns a mask of every piece of a color
+    pub fn get_pieces(&self, color: Color) -> Bitboard {
+        let offset = color.offset();
+        let pieces_u64 = self.bitboards[offset..offset + 6]
             .iter()
             .fold(0u64, |acc, b| acc | b.to_u64());
-        Bitboard(w_pieces_u64)
+        Bitboard(pieces_u64)
+    }
+
+    /// Checks if a color has a piece other than pawns and the king,
+    /// without one a null move can miss a zugzwang
+    pub fn has_non_pawn_material(&self, color: Color) -> bool {
+        [Role::Bishop, Role::Knight, Role::Rook, Role::Queen]
+            .into_iter()
+            .any(|role| self.bitboards[Piece::new(role, color) as usize].to_u64() != 0)
     }
 
-    pub fn get_b_pieces(&self) -> Bitboard {
-        let b_pieces_u64 = self.get_bitboards()[6..12]
+    /// Returns the piece on the square, `None` if it is empty
+    pub fn piece_on(&self, sq: u16) -> Option<Piece> {
+        self.bitboards
             .iter()
-            .fold(0u64, |acc, b| acc | b.to_u64());
-        Bitboard(b_pieces_u64)
+            .position(|bitboard| bitboard.get_bit(sq))
+            .and_then(|i| Piece::try_from(i as u8).ok())
     }
 
     pub fn get_white_to_move(&self) -> bool {
         self.white_to_move
     }
 
+    pub fn side_to_move(&self) -> Color {
+        if self.white_to_move {
+            Color::White
+        } else {
+            Color::Black
+        }
+    }
+
     pub fn set_white_to_move(&mut self, white: bool) {
+        if self.white_to_move != white {
+            self.hash ^= ZOBRIST.black_to_move;
+        }
         self.white_to_move = white;
     }
 
@@ -213,6 +269,8 @@ impl ChessBoard {
     }
 
     pub fn set_castling_rights(&mut self, rights: u8) {
+        self.hash ^=
+            ZOBRIST.castling[self.castling_rights as usize] ^ ZOBRIST.castling[rights as usize];
         self.castling_rights = rights;
     }
 
@@ -221,10 +279,72 @@ impl ChessBoard {
     }
 
     pub fn set_en_passant(&mut self, index: u16) {
+        self.hash ^= ZOBRIST.en_passant(self.en_passant) ^ ZOBRIST.en_passant(index);
         self.en_passant = index;
     }
 
-    pub fn to_fen(&self) -> String {
+    /// Square of the pawn that `color` takes by capturing en passant on `en_passant`,
+    /// it stands right in front of the en passant square from the view of the opponent
+    pub fn en_passant_pawn_square(en_passant: u16, color: Color) -> u16 {
+        match color {
+            Color::White => en_passant + 8,
+            Color::Black => en_passant - 8,
+        }
+    }
+
+    pub fn get_halfmove_clock(&self) -> u16 {
+        self.halfmove_clock
+    }
+
+    pub fn get_fullmove_number(&self) -> u16 {
+        self.fullmove_number
+    }
+
+    pub fn get_hash(&self) -> u64 {
+        self.hash
+    }
+
+    /// Calculates the Zobrist key of the position from scratch
+    pub fn calc_hash(&self) -> u64 {
+        let mut hash = 0;
+        for (piece, bitboard) in self.bitboards.iter().enumerate() {
+            for sq in bitboard {
+                hash ^= ZOBRIST.piece(piece, sq);
+            }
+        }
+        if !self.white_to_move {
+            hash ^= ZOBRIST.black_to_move;
+        }
+        hash ^= ZOBRIST.castling[self.castling_rights as usize];
+        hash ^= ZOBRIST.en_passant(self.en_passant);
+        hash
+    }
+
+    /// Places a piece on an empty square, updating the Zobrist key
+    fn add_piece(&mut self, piece: usize, sq: u16) {
+        if !self.bitboards[piece].get_bit(sq) {
+            self.bitboards[piece].set_bit(sq);
+            self.hash ^= ZOBRIST.piece(piece, sq);
+        }
+    }
+
+    /// Removes a piece from a square if it is there, updating the Zobrist key
+    pub(crate) fn remove_piece(&mut self, piece: usize, sq: u16) {
+        if self.bitboards[piece].get_bit(sq) {
+            self.bitboards[piece].clear_bit(sq);
+            self.hash ^= ZOBRIST.piece(piece, sq);
+        }
+    }
+
+    /// Flips the bits of the mask in the bitboard of the piece, updating the Zobrist key
+    fn toggle_pieces(&mut self, piece: usize, mask: u64) {
+        self.bitboards[piece] ^= mask;
+        for sq in Bitboard::new(mask) {
+            self.hash ^= ZOBRIST.piece(piece, sq);
+        }
+    }
+
+    pub fn to_fen(self) -> String {
         let mut fen = String::new();
 
         for rank_index in 0..8 {
@@ -288,207 +408,177 @@ impl ChessBoard {
         // En passant
         fen.push(' ');
         if self.en_passant < 64 {
-            fen.push_str(&self.en_passant.to_string());
+            fen.push_str(&square_name(self.en_passant));
         } else {
             fen.push('-');
         }
 
-        // Halfmove clock / fullmove number (set to 0/1 for now)
-        fen.push_str(" 0 1");
-
-        return fen;
-    }
-
-    pub fn make_move(&mut self, mv: ChessMove) {
-        self.white_to_move = !self.white_to_move;
+        // Halfmove clock / fullmove number
+        fen.push_str(&format!(
+            " {} {}",
+            self.halfmove_clock, self.fullmove_number
+        ));
+
+        fen
+    }
+
+    /// Makes the move in place and returns what is needed to take it back with `unmake_move`
+    pub fn make_move(&mut self, mv: ChessMove) -> Undo {
+        let color = self.side_to_move();
+        let mut undo = Undo {
+            captured: None,
+            castling_rights: self.castling_rights,
+            en_passant: self.en_passant,
+            halfmove_clock: self.halfmove_clock,
+            fullmove_number: self.fullmove_number,
+            hash: self.hash,
+        };
+        self.set_white_to_move(!self.white_to_move);
+        // the counters stop at their maximum, a FEN may start close to it
+        if color == Color::Black {
+            self.fullmove_number = self.fullmove_number.saturating_add(1);
+        }
 
         let curr_sq = mv.get_curr_square_as_index();
         let dest_sq = mv.get_dest_square_as_index();
+        let Some(piece) = self.piece_of(color, curr_sq) else {
+            return undo;
+        };
+        self.halfmove_clock = self.halfmove_clock.saturating_add(1);
 
         // checking if it is a castling move, removing castling rights for kingmoves
-        if self.bitboards[Piece::WhiteKing as usize].get_bit(curr_sq) {
-            match dest_sq {
-                // g1
-                62u16 => {
-                    self.white_castle_kingside();
-                    return;
-                }
-                // c1
-                58u16 => {
-                    self.white_castle_queenside();
-                    return;
-                }
-                _ => {
-                    self.castling_rights &= castling::BLACK_BOTH;
-                }
+        if piece.role() == Role::King {
+            // the king moves two squares from e1 or e8
+            if curr_sq & 0b111 == 4 && abs_diff_u16(curr_sq, dest_sq) == 2 {
+                self.castle(color, dest_sq > curr_sq);
+                return undo;
             }
+            self.set_castling_rights(self.castling_rights & !castling::rights_of(color));
         }
 
-        if self.bitboards[Piece::BlackKing as usize].get_bit(curr_sq) {
-            match dest_sq {
-                // g8
-                6u16 => {
-                    self.black_castle_kingside();
-                    return;
-                }
-                // c8
-                2u16 => {
-                    self.black_castle_queenside();
-                    return;
-                }
-                _ => {
-                    self.castling_rights &= castling::WHITE_BOTH;
-                }
-            }
-        }
-
-        // checking for castling rights when rook moves/moved
-        match curr_sq {
-            56 => self.castling_rights &= !castling::WHITE_K,
-            63 => self.castling_rights &= !castling::WHITE_Q,
-            0 => self.castling_rights &= !castling::BLACK_K,
-            7 => self.castling_rights &= !castling::BLACK_Q,
-            _ => {}
-        }
-
-        // handling promotion moves, intern and if passed by gui
-        let four_msb = mv.get_four_msb();
-        if four_msb != 0 {
-            if curr_sq > dest_sq {
-                self.bitboards[Piece::WhitePawn as usize].clear_bit(curr_sq);
-                self.bitboards[Piece::BlackBishop as usize].clear_bit(dest_sq);
-                self.bitboards[Piece::BlackKnight as usize].clear_bit(dest_sq);
-                self.bitboards[Piece::BlackRook as usize].clear_bit(dest_sq);
-                self.bitboards[Piece::BlackQueen as usize].clear_bit(dest_sq);
-                match four_msb {
-                    0b0001 => {
-                        self.bitboards[Piece::WhiteKnight as usize].set_bit(dest_sq);
-                        self.en_passant = 64;
-                        return;
-                    }
-                    0b0010 => {
-                        self.bitboards[Piece::WhiteBishop as usize].set_bit(dest_sq);
-                        self.en_passant = 64;
-                        return;
-                    }
-                    0b0100 => {
-                        self.bitboards[Piece::WhiteRook as usize].set_bit(dest_sq);
-                        self.en_passant = 64;
-                        return;
-                    }
-                    0b1000 => {
-                        self.bitboards[Piece::WhiteQueen as usize].set_bit(dest_sq);
-                        self.en_passant = 64;
-                        return;
-                    }
-                    _ => {}
-                }
-            } else {
-                self.bitboards[Piece::BlackPawn as usize].clear_bit(curr_sq);
-                self.bitboards[Piece::WhiteBishop as usize].clear_bit(dest_sq);
-                self.bitboards[Piece::WhiteKnight as usize].clear_bit(dest_sq);
-                self.bitboards[Piece::WhiteRook as usize].clear_bit(dest_sq);
-                self.bitboards[Piece::WhiteQueen as usize].clear_bit(dest_sq);
-                match four_msb {
-                    0b0001 => {
-                        self.bitboards[Piece::BlackKnight as usize].set_bit(dest_sq);
-                        self.en_passant = 64;
-                        return;
-                    }
-                    0b0010 => {
-                        self.bitboards[Piece::BlackBishop as usize].set_bit(dest_sq);
-                        self.en_passant = 64;
-                        return;
-                    }
-                    0b0100 => {
-                        self.bitboards[Piece::BlackRook as usize].set_bit(dest_sq);
-                        self.en_passant = 64;
-                        return;
-                    }
-                    0b1000 => {
-                        self.bitboards[Piece::BlackQueen as usize].set_bit(dest_sq);
-                        self.en_passant = 64;
-                        return;
-                    }
-                    _ => {}
-                }
+        // checking for castling rights when a rook moves or gets captured on its starting square
+        for sq in [curr_sq, dest_sq] {
+            match sq {
+                56 => self.set_castling_rights(self.castling_rights & !castling::WHITE_Q),
+                63 => self.set_castling_rights(self.castling_rights & !castling::WHITE_K),
+                0 => self.set_castling_rights(self.castling_rights & !castling::BLACK_Q),
+                7 => self.set_castling_rights(self.castling_rights & !castling::BLACK_K),
+                _ => {}
             }
         }
 
-        // checking for en passant availability
-        if abs_diff_u16(curr_sq, dest_sq) == 16
-            && (self.bitboards[Piece::WhitePawn as usize].get_bit(curr_sq)
-                || self.bitboards[Piece::BlackPawn as usize].get_bit(curr_sq))
-        {
-            self.en_passant = (curr_sq + dest_sq) >> 1;
-            // already can make move here, since we know it's a pawn move
-            // checking for pawn color move
-            if curr_sq > dest_sq {
-                self.bitboards[Piece::WhitePawn as usize].clear_bit(curr_sq);
-                self.bitboards[Piece::WhitePawn as usize].set_bit(dest_sq);
-            } else {
-                self.bitboards[Piece::BlackPawn as usize].clear_bit(curr_sq);
-                self.bitboards[Piece::BlackPawn as usize].set_bit(dest_sq);
+        let en_passant = self.en_passant;
+        self.set_en_passant(64);
+        if piece.role() == Role::Pawn {
+            self.halfmove_clock = 0;
+            if abs_diff_u16(curr_sq, dest_sq) == 16 {
+                // checking for en passant availability
+                self.set_en_passant((curr_sq + dest_sq) >> 1);
+            } else if dest_sq == en_passant {
+                // taking a pawn with en passant, it is next to the origin square
+                let captured_sq = Self::en_passant_pawn_square(dest_sq, color);
+                undo.captured = self.piece_of(!color, captured_sq);
+                self.remove_piece(Piece::new(Role::Pawn, !color).into(), captured_sq);
             }
-            return;
-        }
-        // checking for taking a pawn with en passant
-        if dest_sq == self.en_passant
-            && (self.bitboards[Piece::WhitePawn as usize].get_bit(curr_sq)
-                || self.bitboards[Piece::BlackPawn as usize].get_bit(curr_sq))
-        {
-            self.en_passant = 64;
-            // already can make move here, since we know it's a pawn move
-            // checking for pawn color move
-            if curr_sq > dest_sq {
-                self.bitboards[Piece::WhitePawn as usize].clear_bit(curr_sq);
-                self.bitboards[Piece::WhitePawn as usize].set_bit(dest_sq);
-                self.bitboards[Piece::BlackPawn as usize].clear_bit(dest_sq + 8);
-            } else {
-                self.bitboards[Piece::BlackPawn as usize].clear_bit(curr_sq);
-                self.bitboards[Piece::BlackPawn as usize].set_bit(dest_sq);
-                self.bitboards[Piece::WhitePawn as usize].clear_bit(dest_sq - 8);
-            }
-            return;
         }
-        self.en_passant = 64;
 
-        for bitboard in self.bitboards.iter_mut() {
-            // clearing bit to cover capturing
-            bitboard.clear_bit(dest_sq);
-            if bitboard.get_bit(curr_sq) {
-                bitboard.clear_bit(curr_sq);
-                bitboard.set_bit(dest_sq);
-            }
+        // clearing bit to cover capturing
+        if let Some(captured) = self.piece_of(!color, dest_sq) {
+            undo.captured = Some(captured);
+            self.halfmove_clock = 0;
+            self.remove_piece(captured.into(), dest_sq);
         }
+        self.remove_piece(piece.into(), curr_sq);
+        // handling promotion moves, intern and if passed by gui
+        let placed = match mv.promotion() {
+            Some(role) if piece.role() == Role::Pawn => Piece::new(role, color),
+            _ => piece,
+        };
+        self.add_piece(placed.into(), dest_sq);
+        undo
     }
 
-    pub fn white_castle_kingside(&mut self) {
-        self.bitboards[Piece::WhiteKing as usize] ^= 0x0000_0000_0000_000Au64; // Flipping the bits of the squares affected by the Kings position
-        self.bitboards[Piece::WhiteRook as usize] ^= 0x0000_0000_0000_0005u64; // Flipping the bits of the squares affected by the Rooks position
-        self.castling_rights &= castling::BLACK_BOTH;
-        self.en_passant = 64;
-    }
-
-    pub fn white_castle_queenside(&mut self) {
-        self.bitboards[Piece::WhiteQueen as usize] ^= 0x0000_0000_0000_0028u64;
-        self.bitboards[Piece::WhiteRook as usize] ^= 0x0000_0000_0000_0090u64;
-        self.castling_rights &= castling::BLACK_BOTH;
-        self.en_passant = 64;
-    }
+    /// Takes back the move made with `make_move`, restoring the exact position before it
+    pub fn unmake_move(&mut self, mv: ChessMove, undo: Undo) {
+        self.white_to_move = !self.white_to_move;
+        let color = self.side_to_move();
+        let curr_sq = mv.get_curr_square_as_index();
+        let dest_sq = mv.get_dest_square_as_index();
 
-    pub fn black_castle_kingside(&mut self) {
-        self.bitboards[Piece::BlackKing as usize] ^= 0x000A_0000_0000_0000u64;
-        self.bitboards[Piece::BlackRook as usize] ^= 0x0005_0000_0000_0000u64;
-        self.castling_rights &= castling::WHITE_BOTH;
-        self.en_passant = 64;
+        if let Some(placed) = self.piece_of(color, dest_sq) {
+            if placed.role() == Role::King
+                && curr_sq & 0b111 == 4
+                && abs_diff_u16(curr_sq, dest_sq) == 2
+            {
+                // castling again flips the bits of king and rook back
+                self.castle(color, dest_sq > curr_sq);
+            } else {
+                let piece = match mv.promotion() {
+                    Some(role) if placed.role() == role => Piece::new(Role::Pawn, color),
+                    _ => placed,
+                };
+                self.bitboards[placed as usize].clear_bit(dest_sq);
+                self.bitboards[piece as usize].set_bit(curr_sq);
+
+                if let Some(captured) = undo.captured {
+                    let captured_sq = if piece.role() == Role::Pawn && dest_sq == undo.en_passant {
+                        Self::en_passant_pawn_square(dest_sq, color)
+                    } else {
+                        dest_sq
+                    };
+                    self.bitboards[captured as usize].set_bit(captured_sq);
+                }
+            }
+        }
+        self.castling_rights = undo.castling_rights;
+        self.en_passant = undo.en_passant;
+        self.halfmove_clock = undo.halfmove_clock;
+        self.fullmove_number = undo.fullmove_number;
+        self.hash = undo.hash;
+    }
+
+    /// Passes the turn to the other side without moving a piece, used by the null-move pruning
+    pub fn make_null_move(&mut self) -> Undo {
+        let undo = Undo {
+            captured: None,
+            castling_rights: self.castling_rights,
+            en_passant: self.en_passant,
+            halfmove_clock: self.halfmove_clock,
+            fullmove_number: self.fullmove_number,
+            hash: self.hash,
+        };
+        self.set_white_to_move(!self.white_to_move);
+        self.set_en_passant(64);
+        undo
     }
 
-    pub fn black_castle_queenside(&mut self) {
-        self.bitboards[Piece::BlackQueen as usize] ^= 0x0028_0000_0000_0000u64;
-        self.bitboards[Piece::BlackRook as usize] ^= 0x0090_0000_0000_0000u64;
-        self.castling_rights &= castling::WHITE_BOTH;
-        self.en_passant = 64;
+    pub fn unmake_null_move(&mut self, undo: Undo) {
+        self.white_to_move = !self.white_to_move;
+        self.en_passant = undo.en_passant;
+        self.hash = undo.hash;
+    }
+
+    /// Returns the piece of a color on the square, `None` if there is none
+    fn piece_of(&self, color: Color, sq: u16) -> Option<Piece> {
+        Role::ALL
+            .into_iter()
+            .map(|role| Piece::new(role, color))
+            .find(|&piece| self.bitboards[piece as usize].get_bit(sq))
+    }
+
+    /// Moves king and rook of a color to their squares after castling
+    pub fn castle(&mut self, color: Color, kingside: bool) {
+        // Flipping the bits of the squares affected by the King and Rook positions, on the 1st rank
+        let (king_mask, rook_mask): (u64, u64) = if kingside { (0x0A, 0x05) } else { (0x28, 0x90) };
+        // the 8th rank holds the most significant bits
+        let shift = match color {
+            Color::White => 0,
+            Color::Black => 56,
+        };
+        self.toggle_pieces(Piece::new(Role::King, color).into(), king_mask << shift);
+        self.toggle_pieces(Piece::new(Role::Rook, color).into(), rook_mask << shift);
+        self.set_castling_rights(self.castling_rights & !castling::rights_of(color));
+        self.set_en_passant(64);
     }
 
     /// Copies the board, makes the move, returns new board
@@ -497,6 +587,24 @@ impl ChessBoard {
         self
     }
 
+    /// Finds the legal move written in coordinate notation, castling is written as a king move like `e1g1`
+    pub fn parse_move(&self, mv: &str) -> Result<ChessMove, MoveParseError> {
+        let parsed = ChessMove::try_from_str(mv.trim())
+            .ok_or_else(|| MoveParseError::Malformed(mv.to_string()))?;
+        if self.all_possible_moves().contains(&parsed) {
+            Ok(parsed)
+        } else {
+            Err(MoveParseError::Illegal(parsed))
+        }
+    }
+
+    /// Plays the move written in coordinate notation if it is legal, unlike `make_move`
+    /// which applies whatever it is given
+    pub fn try_make_move(&mut self, mv: &str) -> Result<Undo, MoveParseError> {
+        let mv = self.parse_move(mv)?;
+        Ok(self.make_move(mv))
+    }
+
     pub fn evaluate_position(&self) -> i32 {
         let mut res = 0;
         for (pc, bitboard) in self.get_bitboards().iter().enumerate() {
@@ -506,6 +614,15 @@ impl ChessBoard {
         }
         res
     }
+
+    /// Evaluation from the view of the side to move, as needed by the negamax search
+    pub fn evaluate_relative(&self) -> i32 {
+        if self.white_to_move {
+            self.evaluate_position()
+        } else {
+            -self.evaluate_position()
+        }
+    }
 }
 
 #[derive(Debug, Copy, Clone, PartialEq, Eq)]
@@ -529,7 +646,7 @@ impl ChessBoard {
 /// ```
 pub struct Bitboard(u64);
 impl Bitboard {
-    pub fn new(val: u64) -> Self {
+    pub const fn new(val: u64) -> Self {
         Self(val)
     }
     pub fn get_bit(&self, index: u16) -> bool {
@@ -538,7 +655,7 @@ impl Bitboard {
     }
     pub fn get_bit_manual(&self, file: i8, rank: i8) -> bool {
         let index = -(rank - 8) * 8 + file - 1;
-        if index >= 64 || index < 0 {
+        if !(0..64).contains(&index) {
             panic!("Bit index out of range for u64: {}", index);
         }
         let mask = 1u64 << index;
@@ -635,7 +752,7 @@ impl IntoIterator for Bitboard {
     }
 }
 
-impl<'a> IntoIterator for &'a Bitboard {
+impl IntoIterator for &Bitboard {
     type Item = u16;
     type IntoIter = BitboardIterator;
 
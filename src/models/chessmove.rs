@@ -1,6 +1,9 @@
+use core::fmt;
+
 use crate::interface::{file_to_i8, i8_to_file, i8_to_rank, rank_to_i8};
+use crate::models::piece::Role;
 
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
 /// A Chessmove in coordinate Notation decoded in a 16 Bit unsigned Integer.
 ///
 /// - The most significant four Bit are used for encoding specalties
@@ -15,10 +18,11 @@ use crate::interface::{file_to_i8, i8_to_file, i8_to_rank, rank_to_i8};
 /// ```0b0010_xxxxxx_yyyyyy``` Bishop <br>
 /// ```0b0100_xxxxxx_yyyyyy``` Rook <br>
 /// ```0b1000_xxxxxx_yyyyyy``` Queen <br>
-
 pub struct ChessMove(u16);
+// allowed dead code, since only some smaller conversion functions aren't used
+#[allow(dead_code)]
 impl ChessMove {
-    pub fn new(u: u16) -> Self {
+    pub const fn new(u: u16) -> Self {
         Self(u)
     }
     pub fn from_curr_and_dest(u_curr: u16, u_dest: u16) -> Self {
@@ -30,38 +34,43 @@ impl ChessMove {
         let u_to: u16 = -(rank_to_i8(dest.rank) - 8) as u16 * 8 + file_to_i8(dest.file) as u16 - 1;
         Self(u_from + u_to)
     }
+    /// Reads a move in coordinate notation like `e2e4` or `e7e8q`, panics if it is malformed
     pub fn from_str(mv: &str) -> Self {
-        let mut chars = mv.chars();
-
-        let curr_file = chars.next().unwrap();
-        let curr_rank = chars.next().unwrap();
-        let dest_file = chars.next().unwrap();
-        let dest_rank = chars.next().unwrap();
-
-        let curr_sq = SquareChar::new(curr_rank, curr_file);
-        let dest_sq = SquareChar::new(dest_rank, dest_file);
+        Self::try_from_str(mv).unwrap_or_else(|| panic!("Invalid move '{}'", mv))
+    }
+    /// Reads a move in coordinate notation like `e2e4` or `e7e8q`, `None` if it is malformed. <br>
+    /// The move isn't checked against a position, see `ChessBoard::parse_move`.
+    pub fn try_from_str(mv: &str) -> Option<Self> {
+        let square = |file: u8, rank: u8| {
+            ((b'a'..=b'h').contains(&file) && (b'1'..=b'8').contains(&rank))
+                .then(|| SquareChar::new(rank as char, file as char))
+        };
+        let (curr_sq, dest_sq, promotion) = match *mv.as_bytes() {
+            [curr_file, curr_rank, dest_file, dest_rank] => (
+                square(curr_file, curr_rank)?,
+                square(dest_file, dest_rank)?,
+                None,
+            ),
+            [curr_file, curr_rank, dest_file, dest_rank, promotion] => (
+                square(curr_file, curr_rank)?,
+                square(dest_file, dest_rank)?,
+                Some(promotion),
+            ),
+            _ => return None,
+        };
 
         // encoding promotions
-        if mv.len() == 5 {
-            let encoding_str = chars.next().unwrap();
-            let mv_int = ChessMove::from_square(curr_sq, dest_sq).0;
-            match encoding_str {
-                'n' => {
-                    return ChessMove::new(0b0001_000000_000000 | mv_int);
-                }
-                'b' => {
-                    return ChessMove::new(0b0010_000000_000000 | mv_int);
-                }
-                'r' => {
-                    return ChessMove::new(0b0100_000000_000000 | mv_int);
-                }
-                'q' => {
-                    return ChessMove::new(0b1000_000000_000000 | mv_int);
-                }
-                _ => {}
-            }
-        }
-        ChessMove::from_square(curr_sq, dest_sq)
+        let encoding = match promotion {
+            None => 0,
+            Some(b'n') => 0b0001_000000_000000,
+            Some(b'b') => 0b0010_000000_000000,
+            Some(b'r') => 0b0100_000000_000000,
+            Some(b'q') => 0b1000_000000_000000,
+            Some(_) => return None,
+        };
+        Some(ChessMove::new(
+            encoding | ChessMove::from_square(curr_sq, dest_sq).0,
+        ))
     }
     pub fn get_curr_square_as_index(&self) -> u16 {
         (self.0 & 0b0000_111111_000000) >> 6
@@ -72,13 +81,23 @@ impl ChessMove {
     pub fn get_four_msb(&self) -> u8 {
         ((self.0 & 0b1111_000000_000000) >> 12) as u8
     }
+    /// Role a pawn promotes to with this move, `None` if it isn't a promotion
+    pub fn promotion(&self) -> Option<Role> {
+        match self.get_four_msb() {
+            0b0001 => Some(Role::Knight),
+            0b0010 => Some(Role::Bishop),
+            0b0100 => Some(Role::Rook),
+            0b1000 => Some(Role::Queen),
+            _ => None,
+        }
+    }
     /// Returns the Move as a String for the UCI Protocol
     pub fn to_str(self) -> String {
         let dest_index = self.0 & 0b0000_000000_111111;
-        let dest_rank = i8_to_rank(-(((dest_index >> 3) as i8 - 8) as i8));
+        let dest_rank = i8_to_rank(-((dest_index >> 3) as i8 - 8));
         let dest_file = i8_to_file(((dest_index % 8) + 1) as i8);
         let curr_index = (self.0 & 0b0000_111111_000000) >> 6;
-        let curr_rank = i8_to_rank(-(((curr_index >> 3) as i8 - 8) as i8));
+        let curr_rank = i8_to_rank(-((curr_index >> 3) as i8 - 8));
         let curr_file = i8_to_file(((curr_index % 8) + 1) as i8);
 
         let mut string = String::new();
@@ -99,7 +118,7 @@ impl ChessMove {
         string
     }
 
-    pub fn to_u16(&self) -> u16 {
+    pub fn to_u16(self) -> u16 {
         self.0
     }
 }
@@ -112,6 +131,26 @@ impl std::ops::BitAnd for ChessMove {
     }
 }
 
+/// Reasons a move sent as text can't be played, returned by `ChessBoard::parse_move`
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum MoveParseError {
+    /// The text isn't a move in coordinate notation
+    Malformed(String),
+    /// The move isn't legal in the position
+    Illegal(ChessMove),
+}
+
+impl fmt::Display for MoveParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MoveParseError::Malformed(mv) => write!(f, "malformed move '{}'", mv),
+            MoveParseError::Illegal(mv) => write!(f, "illegal move '{}'", mv.to_str()),
+        }
+    }
+}
+
+impl std::error::Error for MoveParseError {}
+
 pub enum CastleMove {}
 
 impl CastleMove {
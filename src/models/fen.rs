@@ -0,0 +1,61 @@
+use core::fmt;
+
+use super::{board::ChessBoard, validation::PositionIssue};
+
+/// Reasons a position in Forsyth-Edwards Notation can't be read, returned by `ChessBoard::try_from_fen`
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum FenError {
+    /// A FEN has 6 fields, or 4 if the halfmove clock and fullmove number are left out
+    WrongFieldCount(usize),
+    /// The piece placement doesn't describe 8 ranks
+    WrongRankCount(usize),
+    /// A rank doesn't describe 8 squares, ranks are numbered from 8 to 1
+    BadRankLength {
+        rank: usize,
+        length: usize,
+    },
+    UnknownPiece(char),
+    BadSideToMove(String),
+    BadCastling(String),
+    BadEnPassant(String),
+    BadHalfmoveClock(String),
+    BadFullmoveNumber(String),
+    /// The FEN is well-formed but describes a position that can't come up in a game, only in strict mode
+    InvalidPosition(Vec<PositionIssue>),
+}
+
+impl fmt::Display for FenError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FenError::WrongFieldCount(count) => {
+                write!(f, "expected 4 or 6 fields, found {}", count)
+            }
+            FenError::WrongRankCount(count) => write!(f, "expected 8 ranks, found {}", count),
+            FenError::BadRankLength { rank, length } => {
+                write!(f, "rank {} has {} squares instead of 8", rank, length)
+            }
+            FenError::UnknownPiece(c) => write!(f, "unknown piece '{}'", c),
+            FenError::BadSideToMove(side) => write!(f, "bad side to move '{}'", side),
+            FenError::BadCastling(castling) => write!(f, "bad castling rights '{}'", castling),
+            FenError::BadEnPassant(square) => write!(f, "bad en passant square '{}'", square),
+            FenError::BadHalfmoveClock(clock) => write!(f, "bad halfmove clock '{}'", clock),
+            FenError::BadFullmoveNumber(number) => {
+                write!(f, "bad fullmove number '{}'", number)
+            }
+            FenError::InvalidPosition(issues) => {
+                let issues: Vec<String> = issues.iter().map(|issue| issue.to_string()).collect();
+                write!(f, "invalid position: {}", issues.join(", "))
+            }
+        }
+    }
+}
+
+impl std::error::Error for FenError {}
+
+impl std::str::FromStr for ChessBoard {
+    type Err = FenError;
+
+    fn from_str(fen: &str) -> Result<Self, Self::Err> {
+        Self::try_from_fen(fen)
+    }
+}
@@ -1,4 +1,8 @@
 pub mod board;
 pub mod chessmove;
+pub mod fen;
+pub mod movelist;
 pub mod piece;
 pub mod piecesquaretables;
+pub mod validation;
+pub mod zobrist;
@@ -0,0 +1,77 @@
+use std::ops::{Deref, DerefMut};
+
+use crate::models::chessmove::ChessMove;
+
+/// Maximum number of moves in a position, no legal position has more than 218
+pub const MAX_MOVES: usize = 256;
+
+#[derive(Clone, Debug)]
+/// List of moves with a fixed capacity, stored on the stack so generating moves doesn't allocate. <br>
+/// Dereferences to a slice of the stored moves, which provides iterating, searching and sorting.
+pub struct MoveList {
+    moves: [ChessMove; MAX_MOVES],
+    len: usize,
+}
+
+impl MoveList {
+    pub fn new() -> Self {
+        Self {
+            moves: [ChessMove::new(0); MAX_MOVES],
+            len: 0,
+        }
+    }
+
+    /// Appends a move, panics if the list is full
+    pub fn push(&mut self, mv: ChessMove) {
+        self.moves[self.len] = mv;
+        self.len += 1;
+    }
+}
+
+impl Default for MoveList {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Deref for MoveList {
+    type Target = [ChessMove];
+
+    fn deref(&self) -> &Self::Target {
+        &self.moves[..self.len]
+    }
+}
+
+impl DerefMut for MoveList {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.moves[..self.len]
+    }
+}
+
+impl IntoIterator for MoveList {
+    type Item = ChessMove;
+    type IntoIter = std::iter::Take<std::array::IntoIter<ChessMove, MAX_MOVES>>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.moves.into_iter().take(self.len)
+    }
+}
+
+impl<'a> IntoIterator for &'a MoveList {
+    type Item = &'a ChessMove;
+    type IntoIter = std::slice::Iter<'a, ChessMove>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl FromIterator<ChessMove> for MoveList {
+    fn from_iter<I: IntoIterator<Item = ChessMove>>(iter: I) -> Self {
+        let mut list = MoveList::new();
+        for mv in iter {
+            list.push(mv);
+        }
+        list
+    }
+}
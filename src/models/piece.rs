@@ -1,4 +1,65 @@
-#[derive(Debug)]
+use std::ops::Not;
+
+/// Side a piece belongs to, also used for the side to move
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Color {
+    White = 0,
+    Black = 1,
+}
+
+impl Color {
+    /// Offset of the bitboards of the color, see the order of `Piece`
+    pub fn offset(self) -> usize {
+        self as usize * 6
+    }
+}
+
+impl Not for Color {
+    type Output = Color;
+
+    fn not(self) -> Color {
+        match self {
+            Color::White => Color::Black,
+            Color::Black => Color::White,
+        }
+    }
+}
+
+/// Kind of a piece regardless of its color, in the same order as `Piece`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Role {
+    Pawn = 0,
+    Bishop = 1,
+    Knight = 2,
+    Rook = 3,
+    King = 4,
+    Queen = 5,
+}
+
+impl Role {
+    pub const ALL: [Role; 6] = [
+        Role::Pawn,
+        Role::Bishop,
+        Role::Knight,
+        Role::Rook,
+        Role::King,
+        Role::Queen,
+    ];
+
+    /// Material value in centipawns, without the bonus of the piece-square tables
+    pub fn value(self) -> i32 {
+        match self {
+            Role::Pawn => 100,
+            Role::Bishop => 330,
+            Role::Knight => 320,
+            Role::Rook => 500,
+            Role::King => 20000,
+            Role::Queen => 900,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum Piece {
     WhitePawn = 0,
     WhiteBishop = 1,
@@ -15,6 +76,48 @@ pub enum Piece {
 }
 
 impl Piece {
+    /// Combines a role and a color into the piece
+    pub fn new(role: Role, color: Color) -> Self {
+        Piece::try_from((color.offset() + role as usize) as u8).unwrap()
+    }
+
+    pub fn role(self) -> Role {
+        Role::ALL[self as usize % 6]
+    }
+
+    pub fn color(self) -> Color {
+        if (self as usize) < 6 {
+            Color::White
+        } else {
+            Color::Black
+        }
+    }
+
+    /// Material value in centipawns, without the bonus of the piece-square tables
+    pub fn value(self) -> i32 {
+        self.role().value()
+    }
+
+    /// Reads the letter of a piece in FEN, white pieces are upper case
+    pub fn from_char(c: char) -> Option<Self> {
+        use Piece::*;
+        Some(match c {
+            'P' => WhitePawn,
+            'B' => WhiteBishop,
+            'N' => WhiteKnight,
+            'R' => WhiteRook,
+            'K' => WhiteKing,
+            'Q' => WhiteQueen,
+            'p' => BlackPawn,
+            'b' => BlackBishop,
+            'n' => BlackKnight,
+            'r' => BlackRook,
+            'k' => BlackKing,
+            'q' => BlackQueen,
+            _ => return None,
+        })
+    }
+
     pub fn to_char(self) -> char {
         match self {
             Piece::WhitePawn => 'P',
@@ -33,9 +136,9 @@ impl Piece {
     }
 }
 
-impl Into<usize> for Piece {
-    fn into(self) -> usize {
-        self as usize
+impl From<Piece> for usize {
+    fn from(piece: Piece) -> usize {
+        piece as usize
     }
 }
 
@@ -69,5 +172,13 @@ pub mod castling {
     pub const BLACK_Q: u8 = 0b1000;
     pub const BLACK_BOTH: u8 = 0b1100;
 
+    /// Both castling rights of a color
+    pub fn rights_of(color: super::Color) -> u8 {
+        match color {
+            super::Color::White => WHITE_BOTH,
+            super::Color::Black => BLACK_BOTH,
+        }
+    }
+
     pub const ALL: u8 = 0b1111;
 }
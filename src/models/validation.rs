@@ -0,0 +1,134 @@
+use core::fmt;
+
+use crate::interface::square_name;
+
+use super::{
+    board::{Bitboard, ChessBoard},
+    piece::{Color, Piece, Role, castling},
+};
+
+/// Masks of the first and the eighth rank, no pawn can stand there
+const BACK_RANKS: u64 = 0xff00_0000_0000_00ff;
+
+/// Castling rights with their FEN letter and the home squares of the king and the rook
+const CASTLING_SQUARES: [(u8, char, Color, u16, u16); 4] = [
+    (castling::WHITE_K, 'K', Color::White, 60, 63),
+    (castling::WHITE_Q, 'Q', Color::White, 60, 56),
+    (castling::BLACK_K, 'k', Color::Black, 4, 7),
+    (castling::BLACK_Q, 'q', Color::Black, 4, 0),
+];
+
+/// Something about a position that can't come up in a game, found by `ChessBoard::validate`
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum PositionIssue {
+    /// More than one piece stands on the square
+    OverlappingPieces(u16),
+    /// Each side needs exactly one king
+    KingCount { color: Color, count: u32 },
+    /// A pawn stands on the first or the eighth rank
+    PawnOnBackRank(u16),
+    /// The side that just moved left its king in check
+    OpponentInCheck,
+    /// A castling right, written as its FEN letter, without the king or the rook on its home square
+    CastlingWithoutPieces(char),
+    /// The en passant square isn't behind a pawn that just moved two squares
+    ImpossibleEnPassant(u16),
+}
+
+impl fmt::Display for PositionIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PositionIssue::OverlappingPieces(sq) => {
+                write!(f, "several pieces on {}", square_name(*sq))
+            }
+            PositionIssue::KingCount { color, count } => {
+                write!(f, "{:?} has {} kings", color, count)
+            }
+            PositionIssue::PawnOnBackRank(sq) => write!(f, "pawn on {}", square_name(*sq)),
+            PositionIssue::OpponentInCheck => write!(f, "the side not to move is in check"),
+            PositionIssue::CastlingWithoutPieces(right) => {
+                write!(f, "castling right {} without king and rook", right)
+            }
+            PositionIssue::ImpossibleEnPassant(sq) if *sq < 64 => {
+                write!(f, "impossible en passant square {}", square_name(*sq))
+            }
+            PositionIssue::ImpossibleEnPassant(sq) => {
+                write!(f, "impossible en passant square {}", sq)
+            }
+        }
+    }
+}
+
+impl ChessBoard {
+    /// Checks that the position can come up in a game, returns every issue found. <br>
+    /// Boards built with `from_fen` or the setters aren't checked on their own.
+    pub fn validate(&self) -> Result<(), Vec<PositionIssue>> {
+        let mut issues = Vec::new();
+        let pieces = |role: Role, color: Color| self.get_bitboard(Piece::new(role, color).into());
+
+        let mut occupied = 0u64;
+        let mut overlapping = 0u64;
+        for bitboard in self.get_bitboards() {
+            overlapping |= occupied & bitboard.to_u64();
+            occupied |= bitboard.to_u64();
+        }
+        issues.extend(
+            Bitboard::new(overlapping)
+                .into_iter()
+                .map(PositionIssue::OverlappingPieces),
+        );
+
+        for color in [Color::White, Color::Black] {
+            let count = pieces(Role::King, color).to_u64().count_ones();
+            if count != 1 {
+                issues.push(PositionIssue::KingCount { color, count });
+            }
+        }
+
+        let pawns = pieces(Role::Pawn, Color::White) | pieces(Role::Pawn, Color::Black);
+        issues.extend(
+            Bitboard::new(pawns.to_u64() & BACK_RANKS)
+                .into_iter()
+                .map(PositionIssue::PawnOnBackRank),
+        );
+
+        let color = self.side_to_move();
+        if (pieces(Role::King, !color) & self.calc_attackmask(color)).to_u64() != 0 {
+            issues.push(PositionIssue::OpponentInCheck);
+        }
+
+        for (right, letter, color, king_sq, rook_sq) in CASTLING_SQUARES {
+            if self.get_castling_rights() & right != 0
+                && !(pieces(Role::King, color).get_bit(king_sq)
+                    && pieces(Role::Rook, color).get_bit(rook_sq))
+            {
+                issues.push(PositionIssue::CastlingWithoutPieces(letter));
+            }
+        }
+
+        let en_passant = self.get_en_passant();
+        if en_passant != 64 {
+            let first_sq = match color {
+                Color::White => 16,
+                Color::Black => 40,
+            };
+            // the pawn of the opponent moved from behind the en passant square to the square in front of it
+            let possible = (first_sq..first_sq + 8).contains(&en_passant) && {
+                let pawn_sq = ChessBoard::en_passant_pawn_square(en_passant, color);
+                let origin_sq = 2 * en_passant - pawn_sq;
+                self.piece_on(en_passant).is_none()
+                    && self.piece_on(origin_sq).is_none()
+                    && self.piece_on(pawn_sq) == Some(Piece::new(Role::Pawn, !color))
+            };
+            if !possible {
+                issues.push(PositionIssue::ImpossibleEnPassant(en_passant));
+            }
+        }
+
+        if issues.is_empty() {
+            Ok(())
+        } else {
+            Err(issues)
+        }
+    }
+}
@@ -0,0 +1,68 @@
+/// Random keys for Zobrist hashing, see https://www.chessprogramming.org/Zobrist_Hashing <br>
+/// The key of a position is the XOR of the keys of every piece on its square,
+/// the side to move (if black), the castling rights and the file of the en passant square.
+pub struct ZobristKeys {
+    pub pieces: [[u64; 64]; 12],
+    pub black_to_move: u64,
+    pub castling: [u64; 16],
+    pub en_passant: [u64; 8],
+}
+
+/// Keys are generated at compile time with a fixed seed, so hashes are the same for every run
+pub static ZOBRIST: ZobristKeys = generate_keys();
+
+/// SplitMix64 pseudo random number generator
+const fn next_random(state: &mut u64) -> u64 {
+    *state = state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+    let mut z = *state;
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    z ^ (z >> 31)
+}
+
+const fn generate_keys() -> ZobristKeys {
+    let mut state = 0x4164_616D_4164_616Du64;
+    let mut keys = ZobristKeys {
+        pieces: [[0; 64]; 12],
+        black_to_move: 0,
+        castling: [0; 16],
+        en_passant: [0; 8],
+    };
+
+    let mut piece = 0;
+    while piece < 12 {
+        let mut sq = 0;
+        while sq < 64 {
+            keys.pieces[piece][sq] = next_random(&mut state);
+            sq += 1;
+        }
+        piece += 1;
+    }
+    keys.black_to_move = next_random(&mut state);
+    let mut i = 0;
+    while i < 16 {
+        keys.castling[i] = next_random(&mut state);
+        i += 1;
+    }
+    let mut file = 0;
+    while file < 8 {
+        keys.en_passant[file] = next_random(&mut state);
+        file += 1;
+    }
+    keys
+}
+
+impl ZobristKeys {
+    pub fn piece(&self, piece: usize, sq: u16) -> u64 {
+        self.pieces[piece][sq as usize]
+    }
+
+    /// Key of the en passant square, 0 if no en passant is available
+    pub fn en_passant(&self, en_passant: u16) -> u64 {
+        if en_passant < 64 {
+            self.en_passant[(en_passant & 0b111) as usize]
+        } else {
+            0
+        }
+    }
+}
@@ -22,13 +22,32 @@ fn count_positions(board: ChessBoard, depth: i32) -> u64 {
     for mv in all_moves {
         pos_count += count_positions(board.with_move(mv), depth - 1);
     }
-    return pos_count;
+    pos_count
 }
 
 #[cfg(test)]
 mod tests {
     use super::count_positions;
-    use crate::models::{board::ChessBoard, chessmove::ChessMove};
+    use crate::core::alphabeta::{DRAW_SCORE, INFINITY, MATE_SCORE};
+    use crate::core::info::{MATE_BOUND, SearchInfo, uci_score};
+    use crate::core::limits::GoParams;
+    use crate::core::magic;
+    use crate::core::movepick::{History, MovePicker, is_capture};
+    use crate::core::output::Output;
+    use crate::core::search::{MAX_PLY, Search, SearchSignals};
+    use crate::core::searchthread::SearchThread;
+    use crate::core::selectivity::Selectivity;
+    use crate::core::tt::{Bound, TranspositionTable};
+    use crate::models::{
+        board::{Bitboard, ChessBoard},
+        chessmove::{ChessMove, MoveParseError},
+        fen::FenError,
+        movelist::{MAX_MOVES, MoveList},
+        piece::{Color, Piece, Role},
+        validation::PositionIssue,
+    };
+    use crate::uci::command::{StartPosition, UciCommand};
+    use crate::uci::engine::UciEngine;
 
     // Testing for Shannons number
     // See for reference: https://en.wikipedia.org/wiki/Shannon_number
@@ -51,11 +70,65 @@ mod tests {
         assert_eq!(count_positions(board, 5), 4_865_609);
     }
 
+    #[test]
+    /// Positions of https://www.chessprogramming.org/Perft_Results covering castling, en passant, pins and promotions
+    fn perft_tricky_positions() {
+        let positions = [
+            (
+                "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+                3,
+                97_862,
+            ),
+            ("8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1", 4, 43_238),
+            (
+                "r3k2r/Pppp1ppp/1b3nbN/nP6/BBP1P3/q4N2/Pp1P2PP/R2Q1RK1 w kq - 0 1",
+                3,
+                9_467,
+            ),
+            (
+                "rnbq1k1r/pp1Pbppp/2p5/8/2B5/8/PPP1NnPP/RNBQK2R w KQ - 1 8",
+                3,
+                62_379,
+            ),
+            (
+                "r4rk1/1pp1qppp/p1np1n2/2b1p1B1/2B1P1b1/P1NP1N2/1PP1QPPP/R4RK1 w - - 0 10",
+                3,
+                89_890,
+            ),
+        ];
+        for (fen, depth, nodes) in positions {
+            assert_eq!(
+                count_positions(ChessBoard::from_fen(fen), depth),
+                nodes,
+                "{fen}"
+            );
+        }
+    }
+
+    #[test]
+    /// En passant must not uncover an attack on the own king along the rank of both pawns
+    fn en_passant_discovered_check() {
+        let board = ChessBoard::from_fen("8/8/8/K1pP3r/8/8/8/7k w - c6 0 1");
+        assert!(
+            !board
+                .all_possible_moves()
+                .contains(&ChessMove::from_str("d5c6"))
+        );
+        // capturing the pawn giving check is legal
+        let board = ChessBoard::from_fen("8/8/8/2k5/3Pp3/8/8/4K3 b - d3 0 1");
+        assert!(
+            board
+                .all_possible_moves()
+                .contains(&ChessMove::from_str("e4d3"))
+        );
+    }
+
     #[test]
     /// Compares the `ChessBoard::starting_position()` Chessboard with the one created with `ChessBoard::from_fen()`
     fn from_fen_is_starting_pos() {
         let start_board = ChessBoard::starting_position();
-        let fen_board = ChessBoard::from_fen("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1");
+        let fen_board =
+            ChessBoard::from_fen("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1");
         assert_eq!(start_board, fen_board);
     }
 
@@ -70,7 +143,1365 @@ mod tests {
         start_board.make_move(ChessMove::from_str("f1c4"));
         start_board.make_move(ChessMove::from_str("d8f6"));
         start_board.make_move(ChessMove::from_str("e1g1"));
-        let fen_board = ChessBoard::from_fen("r1b1kbnr/pppp1ppp/2n2q2/4p3/2B1P3/5N2/PPPP1PPP/RNBQ1RK1 b kq - 5 4");
+        let fen_board = ChessBoard::from_fen(
+            "r1b1kbnr/pppp1ppp/2n2q2/4p3/2B1P3/5N2/PPPP1PPP/RNBQ1RK1 b kq - 5 4",
+        );
         assert_eq!(start_board, fen_board);
     }
-}
\ No newline at end of file
+
+    #[test]
+    /// Moving a rook or capturing it on its starting square takes away the castling right of its side
+    fn rook_moves_remove_castling_rights() {
+        use crate::models::piece::castling;
+
+        let board = ChessBoard::from_fen("r3k2r/8/8/8/8/8/8/R3K2R w KQkq - 0 1");
+        let rights = |mv: &str| {
+            board
+                .with_move(ChessMove::from_str(mv))
+                .get_castling_rights()
+        };
+        assert_eq!(rights("a1a2"), castling::ALL & !castling::WHITE_Q);
+        assert_eq!(rights("h1h2"), castling::ALL & !castling::WHITE_K);
+        assert_eq!(rights("a1a8"), castling::WHITE_K | castling::BLACK_K);
+        assert_eq!(rights("h1h8"), castling::WHITE_Q | castling::BLACK_Q);
+    }
+
+    #[test]
+    /// Black castles on the eighth rank, the king passes neither b1 nor b8, so they only have to be empty
+    fn castling_paths() {
+        let mut board = ChessBoard::from_fen("r3k2r/8/8/8/8/8/8/R3K2R b KQkq - 0 1");
+        board.make_move(ChessMove::from_str("e8g8"));
+        assert!(board.to_fen().starts_with("r4rk1/8/8/8/8/8/8/R3K2R "));
+
+        let castles = |fen: &str, mv: &str| {
+            ChessBoard::from_fen(fen)
+                .all_possible_moves()
+                .contains(&ChessMove::from_str(mv))
+        };
+        assert!(castles("1r2k3/8/8/8/8/8/8/R3K3 w Q - 0 1", "e1c1"));
+        assert!(castles("r3k3/8/8/8/8/8/8/1R2K3 b q - 0 1", "e8c8"));
+        assert!(!castles("1r2k3/8/8/8/8/8/8/RN2K3 w Q - 0 1", "e1c1"));
+        assert!(!castles("rn2k3/8/8/8/8/8/8/1R2K3 b q - 0 1", "e8c8"));
+    }
+
+    #[test]
+    /// Queenside castling moves king and rook, the queens stay on their squares
+    fn castling_queenside() {
+        let mut board = ChessBoard::from_fen("r3k2r/3q4/8/8/8/8/3Q4/R3K2R w KQkq - 0 1");
+        board.make_move(ChessMove::from_str("e1c1"));
+        board.make_move(ChessMove::from_str("e8c8"));
+        assert!(board.to_fen().starts_with("2kr3r/3q4/8/8/8/8/3Q4/2KR3R "));
+
+        // a king away from its home square doesn't castle when it steps on g1, c1, g8 or c8
+        let placement = |fen: &str, mv: &str| {
+            let board = ChessBoard::from_fen(fen).with_move(ChessMove::from_str(mv));
+            board.to_fen().split(' ').next().unwrap().to_string()
+        };
+        assert_eq!(
+            placement("4k3/8/8/8/8/8/8/5K1R w - - 0 1", "f1g1"),
+            "4k3/8/8/8/8/8/8/6KR"
+        );
+        assert_eq!(
+            placement("4k3/8/8/8/8/8/8/R2K4 w - - 0 1", "d1c1"),
+            "4k3/8/8/8/8/8/8/R1K5"
+        );
+        assert_eq!(
+            placement("5k1r/8/8/8/8/8/8/4K3 b - - 0 1", "f8g8"),
+            "6kr/8/8/8/8/8/8/4K3"
+        );
+        assert_eq!(
+            placement("r2k4/8/8/8/8/8/8/4K3 b - - 0 1", "d8c8"),
+            "r1k5/8/8/8/8/8/8/4K3"
+        );
+    }
+
+    #[test]
+    /// Every field of a FEN survives parsing and printing, the counters follow the moves
+    fn fen_round_trip_with_counters() {
+        let fens = [
+            "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+            "rnbqkbnr/ppp1p1pp/8/3pPp2/8/8/PPPP1PPP/RNBQKBNR w KQkq f6 0 3",
+            "8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 37 62",
+        ];
+        for fen in fens {
+            assert_eq!(ChessBoard::from_fen(fen).to_fen(), fen);
+        }
+
+        let mut board = ChessBoard::from_fen("4k3/7p/8/8/8/8/4P3/R3K3 w Q - 12 30");
+        board.make_move(ChessMove::from_str("a1a6"));
+        assert_eq!(
+            (board.get_halfmove_clock(), board.get_fullmove_number()),
+            (13, 30)
+        );
+        let undo = board.make_move(ChessMove::from_str("e8d8"));
+        assert_eq!(
+            (board.get_halfmove_clock(), board.get_fullmove_number()),
+            (14, 31)
+        );
+        // both counters are restored when taking back a move
+        board.unmake_move(ChessMove::from_str("e8d8"), undo);
+        assert_eq!(board.to_fen(), "4k3/7p/R7/8/8/8/4P3/4K3 b - - 13 30");
+        // pawn moves and captures reset the clock
+        for mv in ["e8d8", "e2e4", "h7h6", "a6h6"] {
+            board.make_move(ChessMove::from_str(mv));
+        }
+        assert_eq!(board.to_fen(), "3k4/8/7R/8/4P3/8/8/4K3 b - - 0 32");
+    }
+
+    #[test]
+    /// FENs may start with the counters at their maximum, they stay there instead of overflowing
+    fn counters_saturate() {
+        let fen = "4k3/8/8/8/8/8/8/4K3 b - - 65535 65535";
+        let mut board = ChessBoard::from_fen(fen);
+        let undo = board.make_move(ChessMove::from_str("e8e7"));
+        assert_eq!(board.to_fen(), "8/4k3/8/8/8/8/8/4K3 w - - 65535 65535");
+        board.unmake_move(ChessMove::from_str("e8e7"), undo);
+        assert_eq!(board.to_fen(), fen);
+    }
+
+    #[test]
+    fn malformed_fens_are_rejected() {
+        let cases = [
+            (
+                "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq",
+                FenError::WrongFieldCount(3),
+            ),
+            (
+                "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0",
+                FenError::WrongFieldCount(5),
+            ),
+            (
+                "rnbqkbnr/pppppppp/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+                FenError::WrongRankCount(7),
+            ),
+            (
+                "rnbqkbnr/pppppppp/8/8/4P4/8/PPPP1PPP/RNBQKBNR b KQkq - 0 1",
+                FenError::BadRankLength { rank: 4, length: 9 },
+            ),
+            (
+                "rnbqkbnr/pppppppp/8/8/7/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+                FenError::BadRankLength { rank: 4, length: 7 },
+            ),
+            (
+                "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNX w KQkq - 0 1",
+                FenError::UnknownPiece('X'),
+            ),
+            (
+                "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR x KQkq - 0 1",
+                FenError::BadSideToMove("x".to_string()),
+            ),
+            (
+                "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkx - 0 1",
+                FenError::BadCastling("KQkx".to_string()),
+            ),
+            (
+                "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KKq - 0 1",
+                FenError::BadCastling("KKq".to_string()),
+            ),
+            (
+                "rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq i3 0 1",
+                FenError::BadEnPassant("i3".to_string()),
+            ),
+            // the en passant square has to be behind a pawn of the side that just moved
+            (
+                "rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq e6 0 1",
+                FenError::BadEnPassant("e6".to_string()),
+            ),
+            (
+                "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - -1 1",
+                FenError::BadHalfmoveClock("-1".to_string()),
+            ),
+            (
+                "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 0",
+                FenError::BadFullmoveNumber("0".to_string()),
+            ),
+        ];
+        for (fen, err) in cases {
+            assert_eq!(ChessBoard::try_from_fen(fen), Err(err), "{}", fen);
+        }
+
+        // FENs without the counters and parsing through `FromStr` are accepted
+        let board: ChessBoard = "rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq e3"
+            .parse()
+            .unwrap();
+        assert_eq!(
+            board.to_fen(),
+            "rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq e3 0 1"
+        );
+    }
+
+    #[test]
+    fn position_validation() {
+        for fen in [
+            "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+            "rnbqkbnr/ppp1p1pp/8/3pPp2/8/8/PPPP1PPP/RNBQKBNR w KQkq f6 0 3",
+            "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+        ] {
+            assert_eq!(ChessBoard::from_fen(fen).validate(), Ok(()), "{}", fen);
+        }
+
+        let cases = [
+            (
+                "4k3/8/8/8/8/8/8/8 w - - 0 1",
+                vec![PositionIssue::KingCount {
+                    color: Color::White,
+                    count: 0,
+                }],
+            ),
+            (
+                "4k3/8/8/8/8/8/8/K3K3 w - - 0 1",
+                vec![PositionIssue::KingCount {
+                    color: Color::White,
+                    count: 2,
+                }],
+            ),
+            (
+                "P3k3/8/8/8/8/8/8/4K2p w - - 0 1",
+                vec![
+                    PositionIssue::PawnOnBackRank(0),
+                    PositionIssue::PawnOnBackRank(63),
+                ],
+            ),
+            (
+                "4k2R/8/8/8/8/8/8/4K3 w - - 0 1",
+                vec![PositionIssue::OpponentInCheck],
+            ),
+            (
+                "4k3/8/8/8/8/8/8/4K3 w Kq - 0 1",
+                vec![
+                    PositionIssue::CastlingWithoutPieces('K'),
+                    PositionIssue::CastlingWithoutPieces('q'),
+                ],
+            ),
+            (
+                "4k3/8/8/8/8/8/8/3K3R w K - 0 1",
+                vec![PositionIssue::CastlingWithoutPieces('K')],
+            ),
+            // no pawn in front of the square, and a pawn on the square it came from
+            (
+                "4k3/8/8/8/8/8/8/4K3 w - e6 0 1",
+                vec![PositionIssue::ImpossibleEnPassant(20)],
+            ),
+            (
+                "4k3/4p3/8/4p3/8/8/8/4K3 w - e6 0 1",
+                vec![PositionIssue::ImpossibleEnPassant(20)],
+            ),
+        ];
+        for (fen, issues) in cases {
+            assert_eq!(
+                ChessBoard::from_fen(fen).validate(),
+                Err(issues.clone()),
+                "{}",
+                fen
+            );
+            assert_eq!(
+                ChessBoard::try_from_fen_strict(fen),
+                Err(FenError::InvalidPosition(issues))
+            );
+        }
+
+        // boards put together by the setters are checked as well
+        let mut board = ChessBoard::starting_position();
+        board.set_bitboard(
+            Piece::WhiteQueen as usize,
+            Bitboard::new(0x0000_0000_0000_0018),
+        );
+        board.set_en_passant(70);
+        assert_eq!(
+            board.validate(),
+            Err(vec![
+                PositionIssue::OverlappingPieces(60),
+                PositionIssue::ImpossibleEnPassant(70)
+            ])
+        );
+    }
+
+    #[test]
+    /// Without pruning the alpha-beta search has to find the same score as plain minimax at equal depth
+    fn alpha_beta_matches_minimax() {
+        let fens = [
+            (
+                "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+                2,
+            ),
+            (
+                "r1bqkbnr/pppp1ppp/2n5/4p3/2B1P3/5N2/PPPP1PPP/RNBQK2R b KQkq - 3 3",
+                2,
+            ),
+            (
+                "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+                2,
+            ),
+            // smaller positions, where the reference is fast enough for one more ply
+            ("4k3/3q4/8/3p4/8/2N5/3R4/4K3 w - - 0 1", 3),
+            ("4k3/8/3p4/2n1r3/3P4/2N1B3/8/4K3 b - - 0 1", 3),
+            ("8/5k2/3p4/1p1Pp2p/pP2Pp1P/P4P1K/8/8 b - - 0 1", 3),
+        ];
+        for (fen, depth) in fens {
+            let mut board = ChessBoard::from_fen(fen);
+            let minimax_eval = board.minimax(depth);
+            let relative_eval = if board.get_white_to_move() {
+                minimax_eval
+            } else {
+                -minimax_eval
+            };
+            let mut search = Search::new(board, GoParams::default());
+            search.set_selectivity(Selectivity::none());
+            assert_eq!(
+                search.alpha_beta(&mut board, depth, 0, -INFINITY, INFINITY),
+                relative_eval,
+                "{}",
+                fen
+            );
+        }
+    }
+
+    #[test]
+    /// Checks are extended only up to twice the depth of the iteration,
+    /// so a queen giving perpetual check doesn't extend the search forever
+    fn check_extensions_are_bounded() {
+        let with_extensions = |check_extensions: bool| {
+            let mut selectivity = Selectivity::none();
+            selectivity.set("CheckExtensions", check_extensions);
+            selectivity
+        };
+        // the queen can check the king forever, but can't win anything
+        let board = ChessBoard::from_fen("7k/6p1/7p/8/8/8/r5r1/4Q2K w - - 0 1");
+        let mut search = Search::new(board, GoParams::fixed_depth(8));
+        search.set_selectivity(with_extensions(true));
+        assert!(search.run().is_some());
+        assert!((search.seldepth as usize) < MAX_PLY);
+
+        // the mate after a check needs three plies, a search of two plies only finds it with the extension
+        let board = ChessBoard::from_fen("8/4k3/8/3Q2K1/1b5B/8/8/8 w - - 0 1");
+        let search_at = |check_extensions: bool, ply: u16| {
+            let mut search = Search::new(board, GoParams::fixed_depth(2));
+            search.set_selectivity(with_extensions(check_extensions));
+            search.root_depth = 2;
+            let mut board = board;
+            search.alpha_beta(&mut board, 2, ply, -INFINITY, INFINITY)
+        };
+        assert!(search_at(true, 1) >= MATE_BOUND);
+        assert!(search_at(false, 1) < MATE_BOUND);
+        // from twice the depth of the iteration on checks aren't extended any more
+        assert_eq!(search_at(true, 4), search_at(false, 4));
+    }
+
+    #[test]
+    /// Every technique can be toggled by its UCI option and each of them saves nodes
+    /// without losing a tactic
+    fn selectivity_options() {
+        let mut selectivity = Selectivity::none();
+        for name in Selectivity::OPTIONS {
+            assert!(selectivity.set(name, true));
+        }
+        assert_eq!(selectivity, Selectivity::default());
+        assert!(!selectivity.set("Unknown", true));
+
+        let board = ChessBoard::from_fen(
+            "r1bqkbnr/pppp1ppp/2n5/4p3/4P3/5N2/PPPP1PPP/RNBQKB1R w KQkq - 2 3",
+        );
+        let search_nodes = |selectivity: Selectivity| {
+            let mut search = Search::new(board, GoParams::fixed_depth(5));
+            search.set_selectivity(selectivity);
+            search.run();
+            search.nodes
+        };
+        let plain_nodes = search_nodes(Selectivity::none());
+        for name in [
+            "NullMove",
+            "LMR",
+            "ReverseFutility",
+            "Futility",
+            "QSearchPruning",
+        ] {
+            let mut selectivity = Selectivity::none();
+            selectivity.set(name, true);
+            assert!(search_nodes(selectivity) < plain_nodes, "{}", name);
+        }
+        assert!(search_nodes(Selectivity::default()) < plain_nodes / 2);
+
+        // the mate is found with every technique enabled
+        let board = ChessBoard::from_fen("6k1/5ppp/8/8/8/8/5PPP/R5K1 w - - 0 1");
+        let mut search = Search::new(board, GoParams::fixed_depth(4));
+        assert_eq!(search.run().unwrap().to_str(), "a1a8");
+        assert_eq!(search.lines()[0].1, MATE_SCORE - 1);
+    }
+
+    #[test]
+    /// Capturing the undefended queen is the only good move
+    fn best_mv_takes_hanging_queen() {
+        let board = ChessBoard::from_fen("4k3/8/8/3q4/8/8/8/3RK3 w - - 0 1");
+        assert_eq!(board.best_mv(2).to_str(), "d1d5");
+    }
+
+    #[test]
+    fn alpha_beta_scores_mate_in_one() {
+        let mut board = ChessBoard::from_fen("6k1/5ppp/8/8/8/8/8/R5K1 w - - 0 1");
+        let mut search = Search::new(board, GoParams::default());
+        assert_eq!(
+            search.alpha_beta(&mut board, 3, 0, -INFINITY, INFINITY),
+            MATE_SCORE - 1
+        );
+        assert_eq!(board.best_mv(1).to_str(), "a1a8");
+    }
+
+    #[test]
+    fn alpha_beta_scores_stalemate_as_draw() {
+        let mut board = ChessBoard::from_fen("7k/5Q2/6K1/8/8/8/8/8 b - - 0 1");
+        let mut search = Search::new(board, GoParams::default());
+        assert_eq!(
+            search.alpha_beta(&mut board, 3, 0, -INFINITY, INFINITY),
+            DRAW_SCORE
+        );
+    }
+
+    #[test]
+    fn parse_uci_commands_without_arguments() {
+        let cases = [
+            ("uci", UciCommand::Uci),
+            ("isready", UciCommand::IsReady),
+            ("ucinewgame", UciCommand::UciNewGame),
+            ("stop", UciCommand::Stop),
+            ("ponderhit", UciCommand::PonderHit),
+            ("quit", UciCommand::Quit),
+            ("test", UciCommand::Test),
+            ("debug on", UciCommand::Debug(true)),
+            ("debug off", UciCommand::Debug(false)),
+            // extra whitespace, unknown tokens before the command and after it are skipped
+            ("  isready \r\n", UciCommand::IsReady),
+            ("joho debug on", UciCommand::Debug(true)),
+            ("stop now", UciCommand::Stop),
+        ];
+        for (line, command) in cases {
+            assert_eq!(UciCommand::parse(line), Some(command), "{}", line);
+        }
+        for line in ["", "   ", "hello world", "debug", "debug maybe"] {
+            assert_eq!(UciCommand::parse(line), None, "{}", line);
+        }
+    }
+
+    #[test]
+    fn parse_uci_position() {
+        let moves = |moves: &[&str]| {
+            moves
+                .iter()
+                .map(|mv| mv.to_string())
+                .collect::<Vec<String>>()
+        };
+        let fen = "rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq e3 0 1";
+        let cases = [
+            ("position startpos", StartPosition::StartPos, moves(&[])),
+            (
+                "position startpos moves e2e4 e7e5",
+                StartPosition::StartPos,
+                moves(&["e2e4", "e7e5"]),
+            ),
+            (
+                "position   startpos  moves  e2e4\t e7e5 ",
+                StartPosition::StartPos,
+                moves(&["e2e4", "e7e5"]),
+            ),
+            (
+                &format!("position fen {}", fen),
+                StartPosition::Fen(fen.to_string()),
+                moves(&[]),
+            ),
+            (
+                &format!("position fen  {} moves c7c5 g1f3", fen.replace(' ', "  ")),
+                StartPosition::Fen(fen.to_string()),
+                moves(&["c7c5", "g1f3"]),
+            ),
+        ];
+        for (line, start, moves) in cases {
+            assert_eq!(
+                UciCommand::parse(line),
+                Some(UciCommand::Position { start, moves }),
+                "{}",
+                line
+            );
+        }
+        for line in ["position", "position fen", "position moves e2e4"] {
+            assert_eq!(UciCommand::parse(line), None, "{}", line);
+        }
+    }
+
+    #[test]
+    fn parse_uci_setoption_and_register() {
+        let setoption = |name: &str, value: Option<&str>| UciCommand::SetOption {
+            name: name.to_string(),
+            value: value.map(|value| value.to_string()),
+        };
+        assert_eq!(
+            UciCommand::parse("setoption name Hash value 64"),
+            Some(setoption("Hash", Some("64")))
+        );
+        assert_eq!(
+            UciCommand::parse("setoption name Clear Hash"),
+            Some(setoption("Clear Hash", None))
+        );
+        assert_eq!(
+            UciCommand::parse("setoption  name  Book File  value  C:\\my books\\book.bin"),
+            Some(setoption("Book File", Some("C:\\my books\\book.bin")))
+        );
+        assert_eq!(UciCommand::parse("setoption value 64"), None);
+
+        assert_eq!(
+            UciCommand::parse("register later"),
+            Some(UciCommand::Register {
+                later: true,
+                name: None,
+                code: None
+            })
+        );
+        assert_eq!(
+            UciCommand::parse("register name Stefan MK code 4359874324"),
+            Some(UciCommand::Register {
+                later: false,
+                name: Some("Stefan MK".to_string()),
+                code: Some("4359874324".to_string()),
+            })
+        );
+    }
+
+    #[test]
+    fn parse_uci_go() {
+        assert_eq!(
+            UciCommand::parse("go"),
+            Some(UciCommand::Go(GoParams::default()))
+        );
+        assert_eq!(
+            UciCommand::parse("go depth 3"),
+            Some(UciCommand::Go(GoParams::fixed_depth(3)))
+        );
+        assert_eq!(
+            UciCommand::parse("go  infinite searchmoves e2e4 ponder"),
+            Some(UciCommand::Go(GoParams {
+                infinite: true,
+                ponder: true,
+                ..GoParams::default()
+            }))
+        );
+    }
+
+    #[test]
+    fn parse_go_params() {
+        let params = GoParams::parse("go wtime 300000 btime -50 winc 2000 binc 2000 movestogo 20");
+        assert_eq!(params.wtime, Some(300_000));
+        assert_eq!(params.btime, Some(0));
+        assert_eq!(params.winc, Some(2000));
+        assert_eq!(params.binc, Some(2000));
+        assert_eq!(params.movestogo, Some(20));
+        assert!(!params.infinite);
+
+        let params = GoParams::parse("go depth 6 nodes 10000 mate 3 movetime 1000 infinite");
+        assert_eq!(params.depth, Some(6));
+        assert_eq!(params.nodes, Some(10_000));
+        assert_eq!(params.mate, Some(3));
+        assert_eq!(params.movetime, Some(1000));
+        assert!(params.infinite);
+    }
+
+    #[test]
+    fn time_budget_stays_within_clock() {
+        let params = GoParams::parse("go wtime 60000 btime 1000 winc 1000 binc 0");
+        let white = params.time_budget(true).unwrap();
+        assert!(white.soft <= white.hard);
+        assert!(white.hard.as_millis() < 60_000);
+        let black = params.time_budget(false).unwrap();
+        assert!(black.hard.as_millis() < 1000);
+
+        let params = GoParams::parse("go movetime 500");
+        assert!(params.time_budget(true).unwrap().hard.as_millis() <= 500);
+        assert_eq!(GoParams::parse("go infinite").time_budget(true), None);
+        assert_eq!(GoParams::parse("go depth 5").time_budget(true), None);
+    }
+
+    #[test]
+    /// A search limited by nodes or time has to return a move from a completed iteration
+    fn iterative_deepening_respects_limits() {
+        let board = ChessBoard::from_fen(
+            "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+        );
+        let legal_moves = board.all_possible_moves();
+
+        let mut search = Search::new(board, GoParams::parse("go nodes 5000"));
+        let best_mv = search.run().unwrap();
+        assert!(legal_moves.contains(&best_mv));
+        assert!(search.nodes <= 5000 + 1024);
+
+        let start = std::time::Instant::now();
+        let best_mv = Search::new(board, GoParams::parse("go movetime 200"))
+            .run()
+            .unwrap();
+        assert!(legal_moves.contains(&best_mv));
+        assert!(start.elapsed().as_millis() < 1000);
+    }
+
+    #[test]
+    fn go_mate_finds_mate_in_one() {
+        let board = ChessBoard::from_fen("6k1/5ppp/8/8/8/8/8/R5K1 w - - 0 1");
+        let best_mv = Search::new(board, GoParams::parse("go mate 1"))
+            .run()
+            .unwrap();
+        assert_eq!(best_mv.to_str(), "a1a8");
+    }
+
+    #[test]
+    /// An infinite search has to return shortly after the stop signal
+    fn stop_signal_interrupts_infinite_search() {
+        use std::sync::{Arc, atomic::Ordering};
+        use std::time::{Duration, Instant};
+
+        let board = ChessBoard::starting_position();
+        let signals = Arc::new(SearchSignals::default());
+        let mut search = Search::new(board, GoParams::parse("go infinite"));
+        search.set_signals(Arc::clone(&signals));
+
+        let handle = std::thread::spawn(move || search.run());
+        std::thread::sleep(Duration::from_millis(200));
+        let stop_time = Instant::now();
+        signals.stop.store(true, Ordering::Relaxed);
+        let best_mv = handle.join().unwrap().unwrap();
+        assert!(stop_time.elapsed() < Duration::from_millis(500));
+        assert!(board.all_possible_moves().contains(&best_mv));
+    }
+
+    #[test]
+    /// While pondering the time limit doesn't apply, after `ponderhit` it does
+    fn ponder_ignores_time_until_ponderhit() {
+        use std::sync::{Arc, atomic::Ordering};
+        use std::time::Duration;
+
+        let board = ChessBoard::starting_position();
+        let signals = Arc::new(SearchSignals::default());
+        signals.ponder.store(true, Ordering::Relaxed);
+        let mut search = Search::new(board, GoParams::parse("go ponder movetime 50"));
+        search.set_signals(Arc::clone(&signals));
+
+        let handle = std::thread::spawn(move || search.run());
+        std::thread::sleep(Duration::from_millis(300));
+        assert!(!handle.is_finished());
+        signals.ponder.store(false, Ordering::Relaxed);
+        let best_mv = handle.join().unwrap().unwrap();
+        assert!(board.all_possible_moves().contains(&best_mv));
+    }
+
+    #[test]
+    /// The incrementally updated Zobrist key has to match the key calculated from scratch
+    fn zobrist_incremental_matches_recompute() {
+        use rand::{SeedableRng, rngs::StdRng, seq::IndexedRandom};
+
+        let mut rng = StdRng::seed_from_u64(42);
+        let fens = [
+            "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+            "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+            "r3k2r/Pppp1ppp/1b3nbN/nP6/BBP1P3/q4N2/Pp1P2PP/R2Q1RK1 w kq - 0 1",
+            "8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1",
+        ];
+        for fen in fens {
+            for _ in 0..20 {
+                let mut board = ChessBoard::from_fen(fen);
+                assert_eq!(board.get_hash(), board.calc_hash());
+                for _ in 0..80 {
+                    let all_moves = board.all_possible_moves();
+                    let Some(&mv) = all_moves.choose(&mut rng) else {
+                        break;
+                    };
+                    board.make_move(mv);
+                    assert_eq!(
+                        board.get_hash(),
+                        board.calc_hash(),
+                        "{} after {}",
+                        fen,
+                        mv.to_str()
+                    );
+                }
+            }
+        }
+
+        // castling on both sides moves king and rook in the key as well
+        let mut board = ChessBoard::from_fen("r3k2r/3q4/8/8/8/8/3Q4/R3K2R w KQkq - 0 1");
+        for (mv, fen) in [
+            ("e1c1", "r3k2r/3q4/8/8/8/8/3Q4/2KR3R b kq - 0 1"),
+            ("e8c8", "2kr3r/3q4/8/8/8/8/3Q4/2KR3R w - - 0 1"),
+        ] {
+            board.make_move(ChessMove::from_str(mv));
+            assert_eq!(board.get_hash(), board.calc_hash(), "after {}", mv);
+            assert_eq!(
+                board.get_hash(),
+                ChessBoard::from_fen(fen).get_hash(),
+                "after {}",
+                mv
+            );
+        }
+        let mut board = ChessBoard::from_fen("r3k2r/8/8/8/8/8/8/R3K2R w KQkq - 0 1");
+        board.make_move(ChessMove::from_str("e1g1"));
+        board.make_move(ChessMove::from_str("e8g8"));
+        let fen = "r4rk1/8/8/8/8/8/8/R4RK1 w - - 0 1";
+        assert_eq!(board.get_hash(), ChessBoard::from_fen(fen).get_hash());
+    }
+
+    #[test]
+    fn parse_legal_moves() {
+        let mut board = ChessBoard::from_fen("r3k2r/1P6/8/8/8/8/8/R3K2R w KQkq - 0 1");
+        assert_eq!(board.parse_move("e1g1"), Ok(ChessMove::from_str("e1g1")));
+        assert_eq!(board.parse_move("b7a8n"), Ok(ChessMove::from_str("b7a8n")));
+        for mv in ["", "e1", "e1g", "e1g1k", "i1g1", "e9g1", "e1g1qq"] {
+            assert_eq!(
+                board.parse_move(mv),
+                Err(MoveParseError::Malformed(mv.to_string()))
+            );
+        }
+        // empty squares, pieces of the opponent, promotions without a piece and illegal king moves
+        for mv in ["e4e5", "e8g8", "b7b8", "b7a8", "a1a8q"] {
+            assert_eq!(
+                board.parse_move(mv),
+                Err(MoveParseError::Illegal(ChessMove::from_str(mv)))
+            );
+        }
+
+        assert!(board.try_make_move("e1e3").is_err());
+        assert_eq!(board.to_fen(), "r3k2r/1P6/8/8/8/8/8/R3K2R w KQkq - 0 1");
+        board.try_make_move("e1c1").unwrap();
+        assert_eq!(board.to_fen(), "r3k2r/1P6/8/8/8/8/8/2KR3R b kq - 1 1");
+    }
+
+    #[test]
+    /// Making and unmaking any legal move of random games restores the exact position
+    fn make_unmake_round_trip() {
+        use rand::{SeedableRng, rngs::StdRng, seq::IndexedRandom};
+
+        let mut rng = StdRng::seed_from_u64(7);
+        let fens = [
+            "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+            "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+            "r3k2r/Pppp1ppp/1b3nbN/nP6/BBP1P3/q4N2/Pp1P2PP/R2Q1RK1 w kq - 0 1",
+            "8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1",
+        ];
+        for fen in fens {
+            for _ in 0..10 {
+                let mut board = ChessBoard::from_fen(fen);
+                for _ in 0..80 {
+                    let all_moves = board.all_possible_moves();
+                    for &mv in &all_moves {
+                        let before = board;
+                        let undo = board.make_move(mv);
+                        board.unmake_move(mv, undo);
+                        assert_eq!(board, before, "{} after {}", before.to_fen(), mv.to_str());
+                    }
+                    let Some(&mv) = all_moves.choose(&mut rng) else {
+                        break;
+                    };
+                    board.make_move(mv);
+                }
+            }
+        }
+    }
+
+    #[test]
+    /// Transpositions share a key, positions differing only in side to move, castling rights or en passant don't
+    fn zobrist_transpositions() {
+        let mut board_a = ChessBoard::starting_position();
+        for mv in ["g1f3", "g8f6", "b1c3", "b8c6"] {
+            board_a.make_move(ChessMove::from_str(mv));
+        }
+        let mut board_b = ChessBoard::starting_position();
+        for mv in ["b1c3", "b8c6", "g1f3", "g8f6"] {
+            board_b.make_move(ChessMove::from_str(mv));
+        }
+        assert_eq!(board_a.get_hash(), board_b.get_hash());
+
+        let white = ChessBoard::from_fen("4k3/8/8/8/4P3/8/8/4K3 w - - 0 1");
+        let black = ChessBoard::from_fen("4k3/8/8/8/4P3/8/8/4K3 b - - 0 1");
+        let en_passant = ChessBoard::from_fen("4k3/8/8/8/4P3/8/8/4K3 b - e3 0 1");
+        let castling = ChessBoard::from_fen("r3k3/8/8/8/4P3/8/8/4K3 b q - 0 1");
+        let no_castling = ChessBoard::from_fen("r3k3/8/8/8/4P3/8/8/4K3 b - - 0 1");
+        assert_ne!(white.get_hash(), black.get_hash());
+        assert_ne!(black.get_hash(), en_passant.get_hash());
+        assert_ne!(castling.get_hash(), no_castling.get_hash());
+    }
+
+    #[test]
+    fn tt_store_and_probe() {
+        let tt = TranspositionTable::new(1);
+        let board = ChessBoard::starting_position();
+        let key = board.get_hash();
+        let mv = ChessMove::from_str("e2e4");
+        assert_eq!(tt.probe(key, 0), None);
+
+        tt.store(key, Some(mv), 35, 4, Bound::Exact, 0);
+        let entry = tt.probe(key, 0).unwrap();
+        assert_eq!(entry.best_mv, Some(mv));
+        assert_eq!(entry.score, 35);
+        assert_eq!(entry.depth, 4);
+        assert_eq!(entry.bound, Bound::Exact);
+
+        // a shallower search of the same position replaces the entry, but keeps its move
+        tt.store(key, None, 20, 2, Bound::Upper, 0);
+        let entry = tt.probe(key, 0).unwrap();
+        assert_eq!(entry.best_mv, Some(mv));
+        assert_eq!(entry.depth, 2);
+
+        tt.clear();
+        assert_eq!(tt.probe(key, 0), None);
+    }
+
+    #[test]
+    /// Mate scores are stored relative to the position, so they stay correct at a different distance to the root
+    fn tt_adjusts_mate_scores() {
+        let tt = TranspositionTable::new(1);
+        let key = ChessBoard::starting_position().get_hash();
+        // mate in 5 plies from the root, found at ply 2
+        tt.store(key, None, MATE_SCORE - 5, 3, Bound::Exact, 2);
+        assert_eq!(tt.probe(key, 2).unwrap().score, MATE_SCORE - 5);
+        // the same position reached at ply 4 is mated 2 plies later
+        assert_eq!(tt.probe(key, 4).unwrap().score, MATE_SCORE - 7);
+    }
+
+    #[test]
+    /// Searching twice with the same table finds the same move, the second search profits from the stored entries
+    fn search_reuses_tt() {
+        use std::sync::Arc;
+
+        let board = ChessBoard::from_fen(
+            "r1bqkbnr/pppp1ppp/2n5/4p3/2B1P3/5N2/PPPP1PPP/RNBQK2R b KQkq - 3 3",
+        );
+        let tt = Arc::new(TranspositionTable::new(1));
+        let mut first = Search::with_tt(board, GoParams::fixed_depth(4), Arc::clone(&tt));
+        let first_mv = first.run();
+        let mut second = Search::with_tt(board, GoParams::fixed_depth(4), Arc::clone(&tt));
+        let second_mv = second.run();
+        assert_eq!(first_mv, second_mv);
+        assert!(second.nodes < first.nodes);
+    }
+
+    #[test]
+    /// Threads writing the same slots at once never produce an entry mixing the data of two positions
+    fn tt_shared_between_threads() {
+        use std::sync::Arc;
+        use std::thread;
+
+        let tt = Arc::new(TranspositionTable::new(1));
+        let writers: Vec<_> = (0..4u64)
+            .map(|thread_id| {
+                let tt = Arc::clone(&tt);
+                thread::spawn(move || {
+                    for i in 0..100_000u64 {
+                        // few keys, so the threads keep overwriting each other
+                        let key = (i % 64).wrapping_mul(0x9E37_79B9_7F4A_7C15) ^ thread_id;
+                        let score = (key % 1000) as i32;
+                        tt.store(key, None, score, (key % 64) as u8, Bound::Exact, 0);
+                        if let Some(entry) = tt.probe(key, 0) {
+                            assert_eq!(entry.score, score);
+                            assert_eq!(entry.depth, (key % 64) as u8);
+                        }
+                    }
+                })
+            })
+            .collect();
+        for writer in writers {
+            writer.join().unwrap();
+        }
+    }
+
+    #[test]
+    /// A search with helper threads runs to the end and leaves the main thread in charge of `bestmove`
+    fn lazy_smp_search() {
+        let buffer = SharedBuffer::default();
+        let mut search_thread = SearchThread::new(Output::new(buffer.clone()));
+        search_thread.set_threads(4);
+        let board = ChessBoard::from_fen("6k1/5ppp/8/8/8/8/5PPP/R5K1 w - - 0 1");
+        search_thread.start(board, GoParams::fixed_depth(4));
+        search_thread.wait();
+        search_thread.start(board, GoParams::parse("go infinite"));
+        search_thread.stop();
+        let bestmoves: Vec<String> = buffer
+            .lines()
+            .into_iter()
+            .filter(|line| line.starts_with("bestmove"))
+            .collect();
+        assert_eq!(bestmoves.len(), 2);
+        assert_eq!(bestmoves[0], "bestmove a1a8");
+    }
+
+    #[test]
+    /// The main thread reports the nodes of its helpers as well
+    fn helper_nodes_are_counted() {
+        use std::sync::Arc;
+        use std::sync::atomic::AtomicU64;
+
+        let board = ChessBoard::from_fen(
+            "r1bqkbnr/pppp1ppp/2n5/4p3/2B1P3/5N2/PPPP1PPP/RNBQK2R b KQkq - 3 3",
+        );
+        let helper_nodes = Arc::new(AtomicU64::new(0));
+        let mut helper = Search::new(board, GoParams::fixed_depth(4));
+        helper.set_helper(true);
+        helper.set_helper_nodes(Arc::clone(&helper_nodes));
+        helper.run();
+        assert_eq!(helper.total_nodes(), helper.nodes);
+
+        let mut main = Search::new(board, GoParams::fixed_depth(4));
+        main.set_helper_nodes(helper_nodes);
+        main.run();
+        assert!(helper.nodes >= 1024);
+        assert_eq!(main.total_nodes(), main.nodes + helper.nodes / 1024 * 1024);
+    }
+
+    /// Writer the tests keep a handle to, so they can read what the engine wrote from another thread
+    #[derive(Clone, Default)]
+    struct SharedBuffer(std::sync::Arc<std::sync::Mutex<Vec<u8>>>);
+
+    impl std::io::Write for SharedBuffer {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.0.lock().unwrap().extend_from_slice(buf);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    impl SharedBuffer {
+        fn lines(&self) -> Vec<String> {
+            String::from_utf8(self.0.lock().unwrap().clone())
+                .unwrap()
+                .lines()
+                .map(String::from)
+                .collect()
+        }
+    }
+
+    /// Runs a script through a new engine and returns every line it wrote
+    fn uci_session(script: &str) -> Vec<String> {
+        let buffer = SharedBuffer::default();
+        UciEngine::new(buffer.clone()).run(script.as_bytes());
+        buffer.lines()
+    }
+
+    #[test]
+    fn uci_session_search() {
+        let lines = uci_session("uci\nisready\nposition startpos moves e2e4\ngo depth 3\n");
+        let position = |text: &str| lines.iter().position(|line| line == text).unwrap();
+        assert_eq!(lines[0], "id name Adam 0.1");
+        assert!(position("uciok") < position("readyok"));
+        assert!(
+            lines
+                .iter()
+                .any(|line| line.starts_with("option name Threads type spin"))
+        );
+
+        // the end of the script lets the search finish its depth before `bestmove`
+        assert!(lines.iter().any(|line| line.starts_with("info depth 3 ")));
+        let bestmove = lines.last().unwrap();
+        let mv = bestmove.split_whitespace().nth(1).unwrap();
+        assert!(bestmove.starts_with("bestmove "));
+        let board = ChessBoard::starting_position().with_move(ChessMove::from_str("e2e4"));
+        assert!(board.parse_move(mv).is_ok(), "{}", bestmove);
+    }
+
+    #[test]
+    fn uci_session_options_and_fen() {
+        let lines = uci_session(
+            "setoption name MultiPV value 2\nposition fen 6k1/5ppp/8/8/8/8/5PPP/R5K1 w - - 0 1\ngo depth 2\n",
+        );
+        assert!(
+            lines
+                .iter()
+                .any(|line| line.starts_with("info depth 2 ") && line.contains(" multipv 2 "))
+        );
+        assert!(lines.last().unwrap().starts_with("bestmove a1a8"));
+    }
+
+    #[test]
+    /// Malformed commands are answered with an error message and the engine keeps going until `quit`
+    fn uci_session_bad_input() {
+        let lines = uci_session(
+            "position startpos moves e2e4\n\
+             position fen 8/8 w\n\
+             position startpos moves e2e4 e2e4\n\
+             hello\n\
+             setoption name Hash value lots\n\
+             setoption name Contempt value 10\n\
+             go depth 1\n\
+             quit\n\
+             isready\n",
+        );
+        assert_eq!(
+            lines[0],
+            "info string invalid fen: expected 4 or 6 fields, found 2"
+        );
+        assert_eq!(lines[1], "info string invalid moves: illegal move 'e2e4'");
+        assert_eq!(lines[2..4], ["Invalid input: hello", "Continuing"]);
+        assert_eq!(lines[4], "info string invalid Hash value: lots");
+        assert_eq!(lines[5], "info string unknown option: Contempt");
+        // the position of the last valid command is searched, so black is to move
+        let bestmove = lines
+            .iter()
+            .find(|line| line.starts_with("bestmove"))
+            .unwrap();
+        let mv = bestmove.split_whitespace().nth(1).unwrap();
+        let board = ChessBoard::starting_position().with_move(ChessMove::from_str("e2e4"));
+        assert!(board.parse_move(mv).is_ok(), "{}", bestmove);
+        assert_eq!(lines.last().unwrap(), "bye");
+    }
+
+    #[test]
+    /// The test board reads its commands from the input of the engine and writes to its output,
+    /// its `quit` returns to UCI
+    fn uci_session_test_board() {
+        let lines = uci_session("test\ne2e4\ne2e5\nquit\nhello\nisready\nquit\n");
+        let position = |text: &str| lines.iter().position(|line| line == text).unwrap();
+        assert_eq!(lines[3], "8 | r  n  b  q  k  b  n  r |");
+        assert!(position("4 | ·  ·  ·  ·  P  ·  ·  · |") < position("illegal move 'e2e5'"));
+        assert!(position("illegal move 'e2e5'") < position("Exiting"));
+        assert!(position("Exiting") < position("Invalid input: hello"));
+        assert_eq!(lines[lines.len() - 3..], ["Continuing", "readyok", "bye"]);
+    }
+
+    #[test]
+    fn uci_score_format() {
+        assert_eq!(uci_score(35), "cp 35");
+        assert_eq!(uci_score(-120), "cp -120");
+        assert_eq!(uci_score(MATE_SCORE - 1), "mate 1");
+        assert_eq!(uci_score(MATE_SCORE - 3), "mate 2");
+        assert_eq!(uci_score(-MATE_SCORE + 2), "mate -1");
+        assert_eq!(uci_score(-MATE_SCORE + 4), "mate -2");
+    }
+
+    #[test]
+    fn search_info_line() {
+        let info = SearchInfo {
+            depth: 5,
+            seldepth: 7,
+            multipv: None,
+            score: 42,
+            bound: Bound::Exact,
+            nodes: 20_000,
+            time: std::time::Duration::from_millis(400),
+            hashfull: 12,
+            pv: vec![ChessMove::from_str("e2e4"), ChessMove::from_str("e7e5")],
+        };
+        assert_eq!(
+            info.to_string(),
+            "info depth 5 seldepth 7 score cp 42 nodes 20000 nps 50000 time 400 hashfull 12 pv e2e4 e7e5"
+        );
+        let info = SearchInfo {
+            bound: Bound::Lower,
+            ..info
+        };
+        assert!(info.to_string().contains("score cp 42 lowerbound nodes"));
+        let info = SearchInfo {
+            bound: Bound::Upper,
+            ..info
+        };
+        assert!(info.to_string().contains("score cp 42 upperbound nodes"));
+    }
+
+    #[test]
+    /// The root search reports a score outside of its window as bound, inside as exact score
+    fn root_search_window() {
+        let board = ChessBoard::from_fen("4k3/8/8/3q4/8/8/8/3RK3 w - - 0 1");
+        let mut search = Search::new(board, GoParams::default());
+        let (lines, bound) = search.root_search(3, &[], -INFINITY, INFINITY).unwrap();
+        assert_eq!(bound, Bound::Exact);
+        let (best_mv, score, _) = lines[0];
+        assert_eq!(best_mv.to_str(), "d1d5");
+
+        // winning the queen is better than expected
+        let (lines, bound) = search.root_search(3, &[], -INFINITY, 100).unwrap();
+        assert_eq!(bound, Bound::Lower);
+        assert!(lines[0].1 >= 100);
+        // and worse than hoped
+        let (lines, bound) = search.root_search(3, &[], score + 50, score + 100).unwrap();
+        assert_eq!(bound, Bound::Upper);
+        assert!(lines.is_empty());
+        // a window around the score gives the same result as the full window
+        let (lines, bound) = search.root_search(3, &[], score - 25, score + 25).unwrap();
+        assert_eq!(bound, Bound::Exact);
+        assert_eq!((lines[0].0, lines[0].1), (best_mv, score));
+    }
+
+    #[test]
+    /// The principal variation has to be a sequence of legal moves starting with the best move
+    fn principal_variation_is_legal() {
+        let board = ChessBoard::from_fen(
+            "r1bqkbnr/pppp1ppp/2n5/4p3/2B1P3/5N2/PPPP1PPP/RNBQK2R b KQkq - 3 3",
+        );
+        let mut search = Search::new(board, GoParams::fixed_depth(4));
+        let best_mv = search.run().unwrap();
+        let pv = search.pv().to_vec();
+        assert_eq!(pv.first(), Some(&best_mv));
+        assert!(pv.len() > 1);
+        let mut curr_board = board;
+        for mv in pv {
+            assert!(curr_board.all_possible_moves().contains(&mv));
+            curr_board.make_move(mv);
+        }
+    }
+
+    #[test]
+    /// Entries of an earlier search don't cut the principal variation short, it still reaches the full depth
+    fn principal_variation_survives_tt_hits() {
+        use std::sync::Arc;
+
+        let board = ChessBoard::from_fen(
+            "r1bqkbnr/pppp1ppp/2n5/4p3/2B1P3/5N2/PPPP1PPP/RNBQK2R b KQkq - 3 3",
+        );
+        let tt = Arc::new(TranspositionTable::new(1));
+        for _ in 0..2 {
+            let mut search = Search::with_tt(board, GoParams::fixed_depth(4), Arc::clone(&tt));
+            search.run();
+            assert!(search.pv().len() >= 4);
+        }
+    }
+
+    #[test]
+    /// The best of the MultiPV lines has to match the single best move, the lines are ranked by score
+    fn multi_pv_ranks_lines() {
+        let board = ChessBoard::from_fen("4k3/8/8/3q4/8/2n5/8/3RK3 w - - 0 1");
+        let lines = board.multi_pv(2, 3);
+        assert_eq!(lines.len(), 3);
+        assert_eq!(lines[0].0.to_str(), "d1d5");
+        assert_eq!(lines[0].0, board.best_mv(2));
+        for window in lines.windows(2) {
+            assert!(window[0].1 >= window[1].1);
+        }
+        for (mv, _, pv) in &lines {
+            assert_eq!(pv.first(), Some(mv));
+        }
+
+        // asking for more lines than legal moves returns every move
+        let board = ChessBoard::from_fen("7k/8/8/8/8/8/8/K7 w - - 0 1");
+        assert_eq!(board.multi_pv(1, 10).len(), 3);
+    }
+
+    #[test]
+    /// The capture generator has to produce exactly the legal captures and promotions
+    fn capture_moves_are_captures_and_promotions() {
+        let fens = [
+            "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+            "rnbqkbnr/ppp1p1pp/8/3pPp2/8/8/PPPP1PPP/RNBQKBNR w KQkq f6 0 3",
+            "n1n5/PPPk4/8/8/8/8/4Kppp/5N1N b - - 0 1",
+        ];
+        for fen in fens {
+            let board = ChessBoard::from_fen(fen);
+            let captures = board.capture_moves();
+            let expected: Vec<ChessMove> = board
+                .all_possible_moves()
+                .into_iter()
+                .filter(|mv| {
+                    let dest = mv.get_dest_square_as_index();
+                    board.get_all_pieces().get_bit(dest)
+                        || board.get_en_passant() == dest
+                        || mv.get_four_msb() != 0
+                })
+                .collect();
+            assert_eq!(captures.len(), expected.len());
+            assert!(expected.iter().all(|mv| captures.contains(mv)));
+        }
+    }
+
+    #[test]
+    /// Without the quiescence search a depth 1 search would win a pawn defended by a pawn
+    fn quiescence_sees_recapture() {
+        let board = ChessBoard::from_fen("4k3/8/2p5/3p4/8/8/3Q4/4K3 w - - 0 1");
+        assert_ne!(board.best_mv(1).to_str(), "d2d5");
+    }
+
+    #[test]
+    /// The picker returns every legal move once: the hash move, winning captures, killers, quiet moves and losing captures
+    fn move_picker_orders_stages() {
+        let board = ChessBoard::from_fen(
+            "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+        );
+        let all_moves = board.all_possible_moves();
+        let hash_mv = ChessMove::from_str("e1g1");
+        let killer = ChessMove::from_str("a2a3");
+        let killers = [Some(killer), None];
+        let mut picker = MovePicker::new(&board, all_moves.clone(), Some(hash_mv), killers);
+        let mut picked = Vec::new();
+        while let Some(mv) = picker.next(&History::new()) {
+            picked.push(mv);
+        }
+        assert_eq!(picked.len(), all_moves.len());
+        assert!(all_moves.iter().all(|mv| picked.contains(mv)));
+        assert_eq!(picked[0], hash_mv);
+        let good_captures = picked
+            .iter()
+            .filter(|&&mv| is_capture(&board, mv) && board.see_ge(mv, 0))
+            .count();
+        assert!(
+            picked[1..=good_captures]
+                .iter()
+                .all(|&mv| board.see_ge(mv, 0))
+        );
+        assert_eq!(picked[good_captures + 1], killer);
+        // captures losing material come last
+        let bad_captures = picked
+            .iter()
+            .rev()
+            .take_while(|&&mv| is_capture(&board, mv));
+        assert!(bad_captures.count() > 0);
+        // the queen is the most valuable victim, taking it with the pawn is the best capture
+        let board = ChessBoard::from_fen("4k3/8/3q1r2/4P3/8/8/8/4K3 w - - 0 1");
+        let mut picker = MovePicker::captures(&board, board.capture_moves(), false);
+        assert_eq!(picker.next(&History::new()).unwrap().to_str(), "e5d6");
+        // the quiescence search drops the captures losing material
+        let board = ChessBoard::from_fen("4k3/8/2p5/3p4/8/8/3Q4/4K3 w - - 0 1");
+        let mut picker = MovePicker::captures(&board, board.capture_moves(), false);
+        assert_eq!(picker.next(&History::new()).unwrap().to_str(), "d2d5");
+        let mut picker = MovePicker::captures(&board, board.capture_moves(), true);
+        assert_eq!(picker.next(&History::new()), None);
+    }
+
+    #[test]
+    /// Static exchange evaluation with recaptures, x-rays, en passant and promotions
+    fn see_evaluates_exchanges() {
+        let see = |fen: &str, mv: &str| ChessBoard::from_fen(fen).see(ChessMove::from_str(mv));
+        // undefended pawn
+        assert_eq!(
+            see("1k1r4/1pp4p/p7/4p3/8/P5P1/1PP4P/2K1R3 w - - 0 1", "e1e5"),
+            100
+        );
+        // the knight gets lost for a pawn after the whole exchange
+        assert_eq!(
+            see(
+                "1k1r3q/1ppn3p/p4b2/4p3/8/P2N2P1/1PP1R1BP/2K1Q3 w - - 0 1",
+                "d3e5"
+            ),
+            -220
+        );
+        // the rook behind the capturing rook recaptures
+        assert_eq!(see("3rk3/8/8/3p4/8/8/3R4/3RK3 w - - 0 1", "d2d5"), 100);
+        assert_eq!(see("3rk3/3r4/8/3p4/8/8/3R4/3RK3 w - - 0 1", "d2d5"), -400);
+        // the king can't take a defended piece
+        assert_eq!(see("8/8/8/8/8/2k5/3p4/3QK2R w - - 0 1", "d1d2"), 100);
+        assert_eq!(see("8/8/8/8/8/2k5/3p4/3Q1K2 w - - 0 1", "d1d2"), -800);
+        assert_eq!(see("4k3/8/8/3pP3/8/8/8/4K3 w - d6 0 1", "e5d6"), 100);
+        assert_eq!(see("1r2k3/P7/8/8/8/8/8/4K3 w - - 0 1", "a7b8q"), 1300);
+        // a quiet move to an attacked square loses the piece
+        let board = ChessBoard::from_fen("4k3/3p4/8/8/3N4/8/8/4K3 w - - 0 1");
+        assert!(!board.see_ge(ChessMove::from_str("d4e6"), 0));
+        assert!(board.see_ge(ChessMove::from_str("d4b5"), 0));
+    }
+
+    #[test]
+    /// The magic bitboard lookups have to match the ray walking for every square and every occupancy of its rays
+    fn magic_attacks_match_rays() {
+        use rand::{Rng, SeedableRng, rngs::StdRng};
+
+        let mut rng = StdRng::seed_from_u64(42);
+        for sq_index in 0..64 {
+            for (rays, lookup) in [
+                (
+                    ChessBoard::calc_bishop_rays as fn(u16, Bitboard) -> Bitboard,
+                    magic::bishop_attacks as fn(u16, Bitboard) -> Bitboard,
+                ),
+                (ChessBoard::calc_rook_rays, magic::rook_attacks),
+            ] {
+                let ray_mask = rays(sq_index, Bitboard::new(0)).to_u64();
+                // every subset of the rays, with random pieces on the other squares
+                let mut occupancy = 0u64;
+                loop {
+                    let noise = rng.random::<u64>() & !ray_mask;
+                    let occupancy_bb = Bitboard::new(occupancy | noise);
+                    assert_eq!(lookup(sq_index, occupancy_bb), rays(sq_index, occupancy_bb));
+                    occupancy = occupancy.wrapping_sub(ray_mask) & ray_mask;
+                    if occupancy == 0 {
+                        break;
+                    }
+                }
+            }
+            let occupancy = Bitboard::new(rng.random());
+            assert_eq!(
+                magic::queen_attacks(sq_index, occupancy),
+                ChessBoard::calc_bishop_rays(sq_index, occupancy)
+                    | ChessBoard::calc_rook_rays(sq_index, occupancy)
+            );
+        }
+    }
+
+    #[test]
+    /// The precomputed attackmasks have to match the ones calculated with offsets
+    fn attack_tables_match_offsets() {
+        for sq_index in 0..64 {
+            assert_eq!(
+                ChessBoard::calc_knight_attackmask(sq_index),
+                ChessBoard::calc_knight_attackmask_by_offsets(sq_index)
+            );
+            assert_eq!(
+                ChessBoard::calc_king_attackmask(sq_index),
+                ChessBoard::calc_king_attackmask_by_offsets(sq_index)
+            );
+        }
+        // the offset calculations only work for pawns that haven't reached the last rank
+        for (color, squares) in [(Color::White, 8..64), (Color::Black, 0..56)] {
+            for sq_index in squares {
+                assert_eq!(
+                    ChessBoard::calc_pawn_attackmask(sq_index, color),
+                    ChessBoard::calc_pawn_attackmask_by_offsets(sq_index, color)
+                );
+            }
+        }
+        assert_eq!(
+            ChessBoard::calc_pawn_attackmask(3, Color::White),
+            Bitboard::new(0)
+        );
+        assert_eq!(
+            ChessBoard::calc_pawn_attackmask(60, Color::Black),
+            Bitboard::new(0)
+        );
+    }
+
+    #[test]
+    /// A piece splits into its role and color, both sides move with the same code
+    fn pieces_split_into_role_and_color() {
+        for index in 0..12u8 {
+            let piece = Piece::try_from(index).unwrap();
+            assert_eq!(Piece::new(piece.role(), piece.color()), piece);
+        }
+        assert_eq!(Piece::BlackKnight.role(), Role::Knight);
+        assert_eq!(Piece::BlackKnight.color(), Color::Black);
+        assert_eq!(!Color::White, Color::Black);
+
+        let mut board = ChessBoard::starting_position();
+        assert_eq!(board.side_to_move(), Color::White);
+        for mv in ["e2e4", "e7e5", "g1f3", "b8c6", "f1c4", "g8f6", "e1g1"] {
+            board.make_move(ChessMove::from_str(mv));
+        }
+        assert_eq!(board.side_to_move(), Color::Black);
+        assert_eq!(
+            board.to_fen(),
+            "r1bqkb1r/pppp1ppp/2n2n2/4p3/2B1P3/5N2/PPPP1PPP/RNBQ1RK1 b kq - 5 4"
+        );
+        // the mirrored moves give the mirrored position
+        for mv in ["f8c5", "d2d3", "e8g8"] {
+            board.make_move(ChessMove::from_str(mv));
+        }
+        assert_eq!(board.get_pieces(Color::Black).to_u64().count_ones(), 16);
+        assert!(board.get_bitboard(Piece::BlackKing.into()).get_bit(6));
+        assert!(board.get_bitboard(Piece::BlackRook.into()).get_bit(5));
+    }
+
+    #[test]
+    /// The move list behaves like a slice of the pushed moves
+    fn move_list_stores_moves() {
+        let mut list = MoveList::new();
+        assert!(list.is_empty());
+        for mv in ["e2e4", "a2a3", "g1f3"] {
+            list.push(ChessMove::from_str(mv));
+        }
+        assert_eq!(list.len(), 3);
+        assert!(list.contains(&ChessMove::from_str("a2a3")));
+        list.sort_unstable_by_key(|mv| mv.to_str());
+        let sorted: Vec<String> = list.iter().map(|mv| mv.to_str()).collect();
+        assert_eq!(sorted, ["a2a3", "e2e4", "g1f3"]);
+        let collected: MoveList = list.clone().into_iter().rev().collect();
+        assert_eq!(collected[0].to_str(), "g1f3");
+        // a full list holds the maximum number of moves
+        let full: MoveList = std::iter::repeat_n(ChessMove::from_str("e2e4"), MAX_MOVES).collect();
+        assert_eq!(full.len(), MAX_MOVES);
+    }
+}
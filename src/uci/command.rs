@@ -0,0 +1,122 @@
+use crate::core::limits::GoParams;
+
+/// Keywords of every command the engine understands, a line starts at the first of them
+const COMMANDS: [&str; 12] = [
+    "uci",
+    "debug",
+    "isready",
+    "setoption",
+    "register",
+    "ucinewgame",
+    "position",
+    "go",
+    "stop",
+    "ponderhit",
+    "quit",
+    "test",
+];
+
+/// Position a `position` command starts from, before its moves are played
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum StartPosition {
+    StartPos,
+    Fen(String),
+}
+
+/// A command sent by the GUI, see the UCI specification for their meaning
+#[derive(Clone, Debug, PartialEq)]
+pub enum UciCommand {
+    Uci,
+    Debug(bool),
+    IsReady,
+    /// `setoption name <id> [value <x>]`, names and values may contain spaces
+    SetOption {
+        name: String,
+        value: Option<String>,
+    },
+    /// `register later` or `register name <x> code <y>`, the engine needs no registration
+    Register {
+        later: bool,
+        name: Option<String>,
+        code: Option<String>,
+    },
+    UciNewGame,
+    /// `position [startpos | fen <fen>] [moves <move> ...]`, the moves are checked against the position later
+    Position {
+        start: StartPosition,
+        moves: Vec<String>,
+    },
+    Go(GoParams),
+    Stop,
+    PonderHit,
+    Quit,
+    /// Not part of UCI, starts the command line interface for manual testing
+    Test,
+}
+
+impl UciCommand {
+    /// Parses a line sent by the GUI. <br>
+    /// Like the specification asks for, unknown tokens before the command are skipped
+    /// and unknown tokens within it are ignored.
+    /// Returns `None` if the line contains no command or its arguments can't be made sense of.
+    pub fn parse(line: &str) -> Option<Self> {
+        let mut tokens = line
+            .split_whitespace()
+            .skip_while(|token| !COMMANDS.contains(token));
+        let command = tokens.next()?;
+        let args: Vec<&str> = tokens.collect();
+
+        Some(match command {
+            "uci" => UciCommand::Uci,
+            "debug" => match args.first() {
+                Some(&"on") => UciCommand::Debug(true),
+                Some(&"off") => UciCommand::Debug(false),
+                _ => return None,
+            },
+            "isready" => UciCommand::IsReady,
+            "setoption" => {
+                let name = words_after(&args, "name", &["value"])?;
+                let value = words_after(&args, "value", &[]);
+                UciCommand::SetOption { name, value }
+            }
+            "register" => UciCommand::Register {
+                later: args.contains(&"later"),
+                name: words_after(&args, "name", &["code"]),
+                code: words_after(&args, "code", &["name"]),
+            },
+            "ucinewgame" => UciCommand::UciNewGame,
+            "position" => {
+                let start = if args.contains(&"startpos") {
+                    StartPosition::StartPos
+                } else {
+                    StartPosition::Fen(words_after(&args, "fen", &["moves"])?)
+                };
+                let moves = args
+                    .iter()
+                    .skip_while(|&&token| token != "moves")
+                    .skip(1)
+                    .map(|mv| mv.to_string())
+                    .collect();
+                UciCommand::Position { start, moves }
+            }
+            "go" => UciCommand::Go(GoParams::parse(&args.join(" "))),
+            "stop" => UciCommand::Stop,
+            "ponderhit" => UciCommand::PonderHit,
+            "quit" => UciCommand::Quit,
+            "test" => UciCommand::Test,
+            _ => unreachable!("every keyword of COMMANDS is handled"),
+        })
+    }
+}
+
+/// Joins the tokens after `keyword` up to the next token of `until`, with single spaces. <br>
+/// `None` if the keyword is missing or nothing follows it.
+fn words_after(args: &[&str], keyword: &str, until: &[&str]) -> Option<String> {
+    let start = args.iter().position(|&token| token == keyword)? + 1;
+    let words: Vec<&str> = args[start..]
+        .iter()
+        .take_while(|token| !until.contains(token))
+        .copied()
+        .collect();
+    (!words.is_empty()).then(|| words.join(" "))
+}
@@ -0,0 +1,184 @@
+use std::io::{BufRead, Write};
+
+use crate::core::{
+    output::Output,
+    searchthread::{MAX_MULTI_PV, MAX_THREADS, SearchThread},
+    selectivity::Selectivity,
+    tt::{DEFAULT_HASH_MB, MAX_HASH_MB},
+};
+use crate::gui;
+use crate::models::board::ChessBoard;
+use crate::uci::command::{StartPosition, UciCommand};
+
+/// Engine side of the UCI protocol, reads the commands of the GUI from any input
+/// and writes its answers to any output. <br>
+/// Owns the current position and the search thread, which keeps the options set by the GUI.
+pub struct UciEngine {
+    board: ChessBoard,
+    search_thread: SearchThread,
+    output: Output,
+}
+
+impl UciEngine {
+    pub fn new(writer: impl Write + Send + 'static) -> Self {
+        let output = Output::new(writer);
+        Self {
+            board: ChessBoard::starting_position(),
+            search_thread: SearchThread::new(output.clone()),
+            output,
+        }
+    }
+
+    /// Handles every line of the input until `quit`. <br>
+    /// At the end of the input the running search still finishes within its limits,
+    /// so a piped script gets the `bestmove` of its last `go`.
+    pub fn run(&mut self, mut input: impl BufRead) {
+        let mut line = String::new();
+        loop {
+            line.clear();
+            match input.read_line(&mut line) {
+                Ok(0) => break,
+                Ok(_) => {
+                    if !self.handle_line(line.trim_end_matches(['\n', '\r']), &mut input) {
+                        return;
+                    }
+                }
+                Err(error) => {
+                    self.output.line(format!("Error: {}\nExiting now", error));
+                    break;
+                }
+            }
+        }
+        self.search_thread.finish();
+    }
+
+    /// Handles a single line sent by the GUI, returns `false` after `quit`. <br>
+    /// The `test` board reads its own commands from the same input and writes to the same output.
+    pub fn handle_line(&mut self, line: &str, input: &mut impl BufRead) -> bool {
+        let Some(command) = UciCommand::parse(line) else {
+            self.output
+                .line(format!("Invalid input: {}\nContinuing", line.trim()));
+            return true;
+        };
+        match command {
+            UciCommand::Test => {
+                gui::testing(input, &self.output);
+            }
+            UciCommand::Uci => self.identify(),
+            UciCommand::IsReady => {
+                self.output.line("readyok");
+            }
+            UciCommand::UciNewGame => {
+                self.search_thread.clear_tt();
+            }
+            // there is no debug output and no registration
+            UciCommand::Debug(_) | UciCommand::Register { .. } => {}
+            UciCommand::SetOption { name, value } => {
+                self.set_option(&name, &value.unwrap_or_default());
+            }
+            UciCommand::Position { start, moves } => self.set_position(start, &moves),
+            UciCommand::Go(params) => {
+                // Iterative deepening within the limits sent by the GUI, running on the search thread
+                self.search_thread.start(self.board, params);
+            }
+            UciCommand::Stop => {
+                self.search_thread.stop();
+            }
+            UciCommand::PonderHit => {
+                self.search_thread.ponderhit();
+            }
+            UciCommand::Quit => {
+                self.search_thread.stop();
+                self.output.line("bye");
+                return false;
+            }
+        }
+        true
+    }
+
+    /// Answers `uci` with the name of the engine and its options
+    fn identify(&self) {
+        self.output.line("id name Adam 0.1");
+        self.output.line("id author aallbert");
+        self.output.line(format!(
+            "option name Hash type spin default {} min 1 max {}",
+            DEFAULT_HASH_MB, MAX_HASH_MB
+        ));
+        self.output
+            .line("option name Ponder type check default false");
+        self.output.line(format!(
+            "option name MultiPV type spin default 1 min 1 max {}",
+            MAX_MULTI_PV
+        ));
+        self.output.line(format!(
+            "option name Threads type spin default 1 min 1 max {}",
+            MAX_THREADS
+        ));
+        for name in Selectivity::OPTIONS {
+            self.output
+                .line(format!("option name {} type check default true", name));
+        }
+        self.output.line("uciok");
+    }
+
+    fn set_option(&mut self, name: &str, value: &str) {
+        match name {
+            "Hash" => match value.parse::<usize>() {
+                Ok(size_mb) => self.search_thread.resize_tt(size_mb),
+                Err(_) => self
+                    .output
+                    .line(format!("info string invalid Hash value: {}", value)),
+            },
+            "MultiPV" => match value.parse::<usize>() {
+                Ok(multi_pv) => self.search_thread.set_multi_pv(multi_pv),
+                Err(_) => self
+                    .output
+                    .line(format!("info string invalid MultiPV value: {}", value)),
+            },
+            "Threads" => match value.parse::<usize>() {
+                Ok(threads) => self.search_thread.set_threads(threads),
+                Err(_) => self
+                    .output
+                    .line(format!("info string invalid Threads value: {}", value)),
+            },
+            "Ponder" => {}
+            name if Selectivity::OPTIONS.contains(&name) => match value.parse::<bool>() {
+                Ok(enabled) => {
+                    self.search_thread.set_selectivity(name, enabled);
+                }
+                Err(_) => self
+                    .output
+                    .line(format!("info string invalid {} value: {}", name, value)),
+            },
+            other => self
+                .output
+                .line(format!("info string unknown option: {}", other)),
+        }
+    }
+
+    /// Sets up the position of a `position` command,
+    /// a malformed position or an illegal move keeps the previous board instead of crashing the engine
+    fn set_position(&mut self, start: StartPosition, moves: &[String]) {
+        let mut board = match start {
+            StartPosition::StartPos => ChessBoard::starting_position(),
+            StartPosition::Fen(fen) => match ChessBoard::try_from_fen_strict(&fen) {
+                Ok(board) => board,
+                Err(err) => {
+                    self.output
+                        .line(format!("info string invalid fen: {}", err));
+                    return;
+                }
+            },
+        };
+        // Calculate board (stateless)
+        match moves
+            .iter()
+            .try_for_each(|mv| board.try_make_move(mv).map(|_| ()))
+        {
+            Ok(()) => self.board = board,
+            Err(err) => self
+                .output
+                .line(format!("info string invalid moves: {}", err)),
+        }
+    }
+}